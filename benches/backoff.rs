@@ -9,7 +9,7 @@ use conquer_util::BackOff;
 #[bench]
 fn spin_once(b: &mut Bencher) {
     b.iter(|| {
-        let backoff = BackOff::new();
+        let mut backoff = BackOff::new();
         backoff.spin();
     })
 }
@@ -17,7 +17,7 @@ fn spin_once(b: &mut Bencher) {
 #[bench]
 fn spin_full(b: &mut Bencher) {
     b.iter(|| {
-        let backoff = BackOff::new();
+        let mut backoff = BackOff::new();
         while !backoff.advise_yield() {
             backoff.spin();
         }
@@ -27,13 +27,24 @@ fn spin_full(b: &mut Bencher) {
 #[bench]
 fn spin_full_random(b: &mut Bencher) {
     b.iter(|| {
-        let backoff = BackOff::random();
+        let mut backoff = BackOff::random();
         while !backoff.advise_yield() {
             backoff.spin();
         }
     })
 }
 
+#[bench]
+fn spin_full_local(b: &mut Bencher) {
+    b.iter(|| {
+        BackOff::with_local(|backoff| {
+            while !backoff.advise_yield() {
+                backoff.spin();
+            }
+        })
+    })
+}
+
 #[bench]
 fn spin_for(b: &mut Bencher) {
     b.iter(|| {
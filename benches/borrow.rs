@@ -0,0 +1,41 @@
+#![feature(test)]
+
+extern crate test;
+
+use std::cell::RefCell;
+
+use test::Bencher;
+
+use conquer_util::BackOff;
+
+#[bench]
+fn spin_full_unique(b: &mut Bencher) {
+    b.iter(|| {
+        let mut backoff = BackOff::new();
+        while !backoff.advise_yield() {
+            backoff.spin();
+        }
+    })
+}
+
+#[bench]
+fn spin_full_refcell(b: &mut Bencher) {
+    b.iter(|| {
+        let backoff = RefCell::new(BackOff::new());
+        while !backoff.borrow().advise_yield() {
+            backoff.borrow_mut().spin();
+        }
+    })
+}
+
+#[bench]
+fn advise_yield_unique(b: &mut Bencher) {
+    let backoff = BackOff::new();
+    b.iter(|| test::black_box(&backoff).advise_yield())
+}
+
+#[bench]
+fn advise_yield_refcell(b: &mut Bencher) {
+    let backoff = RefCell::new(BackOff::new());
+    b.iter(|| test::black_box(&backoff).borrow().advise_yield())
+}
@@ -98,6 +98,18 @@ impl_align! {
     struct align(0x20000000) Aligned512M; "A thin wrapper type with an alignment of at least 512MB."
 }
 
+/// A thin wrapper type with an alignment of (at least) the size of a cache-line
+/// on the targeted architecture.
+///
+/// On `x86_64` and `aarch64`, an alignment of 128B is used, since these
+/// architectures usually prefetch pairs of adjacent 64B cache-lines.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub type CacheAligned<T> = Aligned128<T>;
+/// A thin wrapper type with an alignment of (at least) the size of a cache-line
+/// on the targeted architecture.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub type CacheAligned<T> = Aligned64<T>;
+
 #[cfg(test)]
 mod tests {
     use core::mem;
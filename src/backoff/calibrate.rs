@@ -0,0 +1,83 @@
+//! Runtime calibration of the cost of a single spin step, which allows
+//! expressing back-off durations in wall-clock time instead of spin counts.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::BackOff;
+
+/// The cached cost of a single spin step in picoseconds (0 if uncalibrated).
+static SPIN_COST_PICOS: AtomicU32 = AtomicU32::new(0);
+
+/// Returns the cached cost of a single spin step in picoseconds and performs
+/// the calibration first, if it has not been done before.
+#[inline]
+pub(super) fn spin_cost_picos() -> u32 {
+    match SPIN_COST_PICOS.load(Ordering::Relaxed) {
+        0 => calibrate(),
+        picos => picos,
+    }
+}
+
+/// Measures the cost of a single spin step, caches and returns it.
+pub(super) fn calibrate() -> u32 {
+    const ITERATIONS: u32 = 10_000;
+    const ROUNDS: u32 = 3;
+
+    // the fastest of multiple rounds is used in order to filter out rounds in
+    // which the thread has been preempted
+    let picos = (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                BackOff::spin_once();
+            }
+
+            start.elapsed().as_nanos() * 1000 / u128::from(ITERATIONS)
+        })
+        .min()
+        .unwrap_or(0)
+        .clamp(1, u128::from(u32::MAX)) as u32;
+
+    SPIN_COST_PICOS.store(picos, Ordering::Relaxed);
+    picos
+}
+
+/// Converts the given picoseconds into a [`Duration`] rounded to the nearest
+/// nanosecond.
+#[inline]
+pub(super) fn picos_to_duration(picos: u32) -> Duration {
+    Duration::from_nanos((u64::from(picos) + 500) / 1000)
+}
+
+/// Returns the smallest exponent `pow` for which spinning `2^pow` steps takes
+/// at least `dur`.
+#[inline]
+pub(super) fn pow_for(dur: Duration) -> u32 {
+    let steps = (dur.as_nanos() * 1000 / u128::from(spin_cost_picos())).max(1);
+    (128 - (steps - 1).leading_zeros()).min(31)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn pow_for() {
+        let cost = super::calibrate();
+        assert!(cost > 0);
+        assert_eq!(super::pow_for(Duration::from_nanos(0)), 0);
+
+        let dur = Duration::from_nanos(u64::from(cost) * 1024 / 1000);
+        let pow = super::pow_for(dur);
+        assert!((9..=11).contains(&pow));
+        assert!(super::pow_for(dur * 4) > pow);
+    }
+
+    #[test]
+    fn picos_to_duration() {
+        assert_eq!(super::picos_to_duration(499), Duration::from_nanos(0));
+        assert_eq!(super::picos_to_duration(500), Duration::from_nanos(1));
+        assert_eq!(super::picos_to_duration(1499), Duration::from_nanos(1));
+    }
+}
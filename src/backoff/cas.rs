@@ -0,0 +1,449 @@
+//! Extension methods for running compare-and-swap (CAS) loops with built-in
+//! back-off on all atomic types in [`core::sync::atomic`].
+
+use core::sync::atomic::Ordering;
+#[cfg(target_has_atomic = "8")]
+use core::sync::atomic::{AtomicBool, AtomicI8, AtomicU8};
+#[cfg(target_has_atomic = "16")]
+use core::sync::atomic::{AtomicI16, AtomicU16};
+#[cfg(target_has_atomic = "32")]
+use core::sync::atomic::{AtomicI32, AtomicU32};
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicI64, AtomicU64};
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::{AtomicIsize, AtomicPtr, AtomicUsize};
+
+use super::{BackOff, BackOffStrategy};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// AtomicRetry
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An extension trait for atomic types, which runs compare-and-swap (CAS)
+/// loops that back off after each failed attempt.
+///
+/// Each failed attempt is followed by a single back-off step, which spins or
+/// yields the current thread (if the `std` feature is enabled and further
+/// spinning is not advisable).
+/// The outcome of each operation is reported to the [`BackOff`] exactly once:
+/// An operation that had to be retried is reported through
+/// [`failure`][BackOff::failure], otherwise through
+/// [`success`][BackOff::success].
+/// A successful operation also resets the [`BackOff`], so the same instance
+/// can be re-used for subsequent operations.
+///
+/// Each atomic type only implements this trait on platforms supporting
+/// atomic compare-and-swap operations of the respective width.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use conquer_util::{AtomicRetry, BackOff};
+///
+/// let counter = AtomicUsize::new(0);
+/// let mut backoff = BackOff::new();
+///
+/// let prev = counter.fetch_update_with_backoff(
+///     &mut backoff,
+///     Ordering::Relaxed,
+///     Ordering::Relaxed,
+///     |curr| Some(curr + 1),
+/// );
+///
+/// assert_eq!(prev, Ok(0));
+/// assert_eq!(counter.load(Ordering::Relaxed), 1);
+/// ```
+pub trait AtomicRetry {
+    /// The type of the value stored in the atomic type.
+    type Value: Copy;
+
+    /// Fetches the value and applies `f` to it, which returns an optional new
+    /// value, until the new value has been successfully stored.
+    ///
+    /// The `set_order` and `fetch_order` arguments describe the memory
+    /// orderings of the successful and failed CAS and the initial load,
+    /// analogous to the `fetch_update` method of the standard library's atomic
+    /// types.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the current value, if `f` returns `None`.
+    fn fetch_update_with_backoff<S: BackOffStrategy>(
+        &self,
+        backoff: &mut BackOff<S>,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        f: impl FnMut(Self::Value) -> Option<Self::Value>,
+    ) -> Result<Self::Value, Self::Value>;
+
+    /// Fetches the value and applies `f` to it, which returns an optional new
+    /// value, until the new value has been successfully stored, but backs off
+    /// at most `max_attempts` times.
+    ///
+    /// See [`fetch_update_with_backoff`][AtomicRetry::fetch_update_with_backoff]
+    /// for details.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the current value, if `f` returns `None` or if the value
+    /// could not be stored after backing off `max_attempts` times.
+    fn fetch_update_bounded<S: BackOffStrategy>(
+        &self,
+        backoff: &mut BackOff<S>,
+        max_attempts: u32,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        f: impl FnMut(Self::Value) -> Option<Self::Value>,
+    ) -> Result<Self::Value, Self::Value>;
+
+    /// Repeatedly attempts to store `new`, if the current value is equal to
+    /// `current`, until it succeeds.
+    ///
+    /// This can be used e.g. for acquiring a spin-lock by swapping `false`
+    /// with `true`.
+    fn compare_exchange_loop<S: BackOffStrategy>(
+        &self,
+        backoff: &mut BackOff<S>,
+        current: Self::Value,
+        new: Self::Value,
+        success: Ordering,
+        failure: Ordering,
+    );
+
+    /// Repeatedly attempts to store `new`, if the current value is equal to
+    /// `current`, until it succeeds, but backs off at most `max_attempts`
+    /// times.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the last observed value, if the value could not be stored
+    /// after backing off `max_attempts` times.
+    fn compare_exchange_loop_bounded<S: BackOffStrategy>(
+        &self,
+        backoff: &mut BackOff<S>,
+        max_attempts: u32,
+        current: Self::Value,
+        new: Self::Value,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Self::Value, Self::Value>;
+}
+
+macro_rules! impl_atomic_retry {
+    ($($(#[$attr:meta])* impl$(<$param:ident>)? for $atomic:ty => $value:ty;)*) => {
+        $(
+            $(#[$attr])*
+            impl$(<$param>)? AtomicRetry for $atomic {
+                type Value = $value;
+
+                #[inline]
+                fn fetch_update_with_backoff<S: BackOffStrategy>(
+                    &self,
+                    backoff: &mut BackOff<S>,
+                    set_order: Ordering,
+                    fetch_order: Ordering,
+                    f: impl FnMut(Self::Value) -> Option<Self::Value>,
+                ) -> Result<Self::Value, Self::Value> {
+                    fetch_update(
+                        backoff,
+                        None,
+                        self.load(fetch_order),
+                        |curr, new| self.compare_exchange_weak(curr, new, set_order, fetch_order),
+                        f,
+                    )
+                }
+
+                #[inline]
+                fn fetch_update_bounded<S: BackOffStrategy>(
+                    &self,
+                    backoff: &mut BackOff<S>,
+                    max_attempts: u32,
+                    set_order: Ordering,
+                    fetch_order: Ordering,
+                    f: impl FnMut(Self::Value) -> Option<Self::Value>,
+                ) -> Result<Self::Value, Self::Value> {
+                    fetch_update(
+                        backoff,
+                        Some(max_attempts),
+                        self.load(fetch_order),
+                        |curr, new| self.compare_exchange_weak(curr, new, set_order, fetch_order),
+                        f,
+                    )
+                }
+
+                #[inline]
+                fn compare_exchange_loop<S: BackOffStrategy>(
+                    &self,
+                    backoff: &mut BackOff<S>,
+                    current: Self::Value,
+                    new: Self::Value,
+                    success: Ordering,
+                    failure: Ordering,
+                ) {
+                    let _ = compare_exchange_loop(backoff, None, || {
+                        self.compare_exchange_weak(current, new, success, failure)
+                    });
+                }
+
+                #[inline]
+                fn compare_exchange_loop_bounded<S: BackOffStrategy>(
+                    &self,
+                    backoff: &mut BackOff<S>,
+                    max_attempts: u32,
+                    current: Self::Value,
+                    new: Self::Value,
+                    success: Ordering,
+                    failure: Ordering,
+                ) -> Result<Self::Value, Self::Value> {
+                    compare_exchange_loop(backoff, Some(max_attempts), || {
+                        self.compare_exchange_weak(current, new, success, failure)
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_atomic_retry! {
+    #[cfg(target_has_atomic = "8")]
+    impl for AtomicBool => bool;
+    #[cfg(target_has_atomic = "8")]
+    impl for AtomicI8 => i8;
+    #[cfg(target_has_atomic = "16")]
+    impl for AtomicI16 => i16;
+    #[cfg(target_has_atomic = "32")]
+    impl for AtomicI32 => i32;
+    #[cfg(target_has_atomic = "64")]
+    impl for AtomicI64 => i64;
+    #[cfg(target_has_atomic = "ptr")]
+    impl for AtomicIsize => isize;
+    #[cfg(target_has_atomic = "8")]
+    impl for AtomicU8 => u8;
+    #[cfg(target_has_atomic = "16")]
+    impl for AtomicU16 => u16;
+    #[cfg(target_has_atomic = "32")]
+    impl for AtomicU32 => u32;
+    #[cfg(target_has_atomic = "64")]
+    impl for AtomicU64 => u64;
+    #[cfg(target_has_atomic = "ptr")]
+    impl for AtomicUsize => usize;
+    #[cfg(target_has_atomic = "ptr")]
+    impl<T> for AtomicPtr<T> => *mut T;
+}
+
+#[inline]
+fn fetch_update<S: BackOffStrategy, T: Copy>(
+    backoff: &mut BackOff<S>,
+    max_attempts: Option<u32>,
+    mut curr: T,
+    cas: impl Fn(T, T) -> Result<T, T>,
+    mut f: impl FnMut(T) -> Option<T>,
+) -> Result<T, T> {
+    let mut attempts = 0;
+    let mut contended = false;
+    while let Some(new) = f(curr) {
+        match cas(curr, new) {
+            Ok(prev) => {
+                report_success(backoff, contended);
+                return Ok(prev);
+            }
+            Err(actual) => {
+                curr = actual;
+                contended = true;
+                if let Some(max_attempts) = max_attempts {
+                    if attempts == max_attempts {
+                        break;
+                    }
+
+                    attempts += 1;
+                }
+
+                backoff.wait();
+            }
+        }
+    }
+
+    if contended {
+        backoff.failure();
+    }
+
+    Err(curr)
+}
+
+#[inline]
+fn compare_exchange_loop<S: BackOffStrategy, T: Copy>(
+    backoff: &mut BackOff<S>,
+    max_attempts: Option<u32>,
+    cas: impl Fn() -> Result<T, T>,
+) -> Result<T, T> {
+    let mut attempts = 0;
+    let mut contended = false;
+    loop {
+        match cas() {
+            Ok(prev) => {
+                report_success(backoff, contended);
+                return Ok(prev);
+            }
+            Err(actual) => {
+                contended = true;
+                if let Some(max_attempts) = max_attempts {
+                    if attempts == max_attempts {
+                        backoff.failure();
+                        return Err(actual);
+                    }
+
+                    attempts += 1;
+                }
+
+                backoff.wait();
+            }
+        }
+    }
+}
+
+/// Reports the outcome of a successful operation exactly once, counting it as
+/// a failure if it had to be retried.
+#[inline]
+fn report_success<S: BackOffStrategy>(backoff: &mut BackOff<S>, contended: bool) {
+    if contended {
+        backoff.failure();
+        backoff.reset();
+    } else {
+        backoff.success();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec::Vec;
+
+    use super::AtomicRetry;
+    use crate::BackOff;
+
+    #[test]
+    fn fetch_update() {
+        const THREADS: usize = 4;
+        const INCREMENTS: usize = 1000;
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let counter = Arc::clone(&counter);
+                thread::spawn(move || {
+                    let mut backoff = BackOff::new();
+                    for _ in 0..INCREMENTS {
+                        let res = counter.fetch_update_with_backoff(
+                            &mut backoff,
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                            |curr| Some(curr + 1),
+                        );
+                        assert!(res.is_ok());
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(counter.load(Ordering::Relaxed), THREADS * INCREMENTS);
+    }
+
+    #[test]
+    fn fetch_update_abort() {
+        let counter = AtomicUsize::new(10);
+        let mut backoff = BackOff::new();
+        let res = counter.fetch_update_bounded(
+            &mut backoff,
+            4,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |curr| if curr < 10 { Some(curr + 1) } else { None },
+        );
+        assert_eq!(res, Err(10));
+    }
+
+    #[test]
+    fn fetch_update_exhausted() {
+        let counter = AtomicUsize::new(0);
+        let mut backoff = BackOff::new();
+        // every attempt loses the race against a concurrent increment
+        let res = counter.fetch_update_bounded(
+            &mut backoff,
+            4,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |curr| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                Some(curr + 100)
+            },
+        );
+        assert_eq!(res, Err(5));
+        assert_eq!(counter.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn fetch_update_adaptive() {
+        let counter = AtomicUsize::new(0);
+        let mut backoff = BackOff::adaptive();
+        // the first two attempts lose the race, the third one succeeds
+        let res = counter.fetch_update_with_backoff(
+            &mut backoff,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |curr| {
+                if curr < 2 {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+                Some(curr + 100)
+            },
+        );
+        assert_eq!(res, Ok(2));
+        assert_eq!(backoff.strategy().offset(), 1);
+
+        let res = counter.fetch_update_with_backoff(
+            &mut backoff,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |curr| Some(curr + 1),
+        );
+        assert_eq!(res, Ok(102));
+        assert_eq!(backoff.strategy().offset(), 0);
+    }
+
+    #[test]
+    fn compare_exchange_loop() {
+        let mut value = 0;
+        let ptr = AtomicPtr::new(core::ptr::null_mut());
+        let mut backoff = BackOff::new();
+        ptr.compare_exchange_loop(
+            &mut backoff,
+            core::ptr::null_mut(),
+            &mut value,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+        assert_eq!(ptr.load(Ordering::Relaxed), &mut value as *mut _);
+
+        let lock = AtomicBool::new(true);
+        let res = lock.compare_exchange_loop_bounded(
+            &mut backoff,
+            4,
+            false,
+            true,
+            Ordering::Acquire,
+            Ordering::Relaxed,
+        );
+        assert_eq!(res, Err(true));
+    }
+}
@@ -0,0 +1,82 @@
+//! An abstraction over monotonic clocks, which allows timed spinning in
+//! `#![no_std]` environments.
+
+use core::time::Duration;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Clock
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A monotonic clock, which can be used for timed spinning, e.g. through
+/// [`BackOff::spin_for_with_clock`][crate::BackOff::spin_for_with_clock].
+///
+/// This allows timed spinning in `#![no_std]` environments, which e.g. provide
+/// a hardware timer.
+/// If the `std` feature is enabled, the [`StdClock`] implementation based on
+/// [`std::time::Instant`] is available.
+///
+/// # Examples
+///
+/// ```
+/// use core::cell::Cell;
+/// use core::time::Duration;
+///
+/// use conquer_util::{BackOff, Clock};
+///
+/// /// A clock that advances by 1 µs each time it is read.
+/// struct Timer(Cell<u64>);
+///
+/// impl Clock for Timer {
+///     type Instant = u64;
+///
+///     fn now(&self) -> u64 {
+///         let now = self.0.get();
+///         self.0.set(now + 1);
+///         now
+///     }
+///
+///     fn add(&self, instant: u64, dur: Duration) -> u64 {
+///         instant + dur.as_micros() as u64
+///     }
+/// }
+///
+/// let timer = Timer(Cell::new(0));
+/// BackOff::spin_for_with_clock(&timer, Duration::from_micros(10));
+/// assert!(timer.now() >= 10);
+/// ```
+pub trait Clock {
+    /// The type of a point in time measured by the clock.
+    type Instant: Copy + Ord;
+
+    /// Returns the current point in time.
+    fn now(&self) -> Self::Instant;
+
+    /// Returns the point in time `dur` after `instant`.
+    fn add(&self, instant: Self::Instant, dur: Duration) -> Self::Instant;
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// StdClock
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The default [`Clock`] implementation based on [`std::time::Instant`].
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub struct StdClock;
+
+/********** impl Clock ****************************************************************************/
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    type Instant = std::time::Instant;
+
+    #[inline]
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+
+    #[inline]
+    fn add(&self, instant: Self::Instant, dur: Duration) -> Self::Instant {
+        instant + dur
+    }
+}
@@ -0,0 +1,144 @@
+//! A back-off type whose parameters are fixed at compile-time.
+
+use core::fmt;
+
+use super::BackOff;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// ConstBackOff
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A type for exponential back-off in tight loops, whose initial and maximum
+/// exponents are fixed at compile-time.
+///
+/// Unlike [`BackOff`], which consults its [`BackOffStrategy`][crate::BackOffStrategy]
+/// in each round, the number of spin steps is determined solely by the `INIT`
+/// and `LIMIT` parameters, so the compiler can fold all bounds into constants.
+/// The number of steps in each round is `2^n`, where `n` starts out at `INIT`
+/// and grows by one with each round until it reaches `LIMIT`.
+/// If `INIT` exceeds `LIMIT`, `LIMIT` is used as initial exponent as well.
+/// Both exponents are capped at 31.
+///
+/// # Examples
+///
+/// ```
+/// use conquer_util::ConstBackOff;
+///
+/// let mut backoff = ConstBackOff::<1, 7>::new();
+/// while !backoff.advise_yield() {
+///     backoff.spin();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ConstBackOff<const INIT: u32, const LIMIT: u32> {
+    pow: u32,
+}
+
+/********** impl Default **************************************************************************/
+
+impl<const INIT: u32, const LIMIT: u32> Default for ConstBackOff<INIT, LIMIT> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/********** impl inherent *************************************************************************/
+
+impl<const INIT: u32, const LIMIT: u32> ConstBackOff<INIT, LIMIT> {
+    /// The effective maximum exponent.
+    pub const LIMIT_POW: u32 = if LIMIT < 31 { LIMIT } else { 31 };
+    /// The effective initial exponent.
+    pub const INIT_POW: u32 = if INIT < Self::LIMIT_POW { INIT } else { Self::LIMIT_POW };
+
+    /// Creates a new [`ConstBackOff`] instance.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { pow: Self::INIT_POW }
+    }
+
+    /// Resets the [`ConstBackOff`] instance to its initial state.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.pow = Self::INIT_POW;
+    }
+
+    /// Spins for a bounded number of steps.
+    ///
+    /// See [`BackOff::spin`] for details.
+    #[inline]
+    pub fn spin(&mut self) {
+        let steps = self.steps();
+        if self.pow < Self::LIMIT_POW {
+            self.pow += 1;
+        }
+
+        for _ in 0..steps {
+            BackOff::spin_once();
+        }
+    }
+
+    /// Returns `true` if further spinning is not advisable and other means such
+    /// as voluntarily yielding the current thread could be more efficient.
+    #[inline]
+    pub const fn advise_yield(&self) -> bool {
+        self.pow >= Self::LIMIT_POW
+    }
+
+    /// Returns the number of steps the next call to
+    /// [`spin`][ConstBackOff::spin] will spin for.
+    #[inline]
+    pub const fn steps(&self) -> u32 {
+        1 << self.pow
+    }
+}
+
+/********** impl Debug ****************************************************************************/
+
+impl<const INIT: u32, const LIMIT: u32> fmt::Debug for ConstBackOff<INIT, LIMIT> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConstBackOff")
+            .field("init_pow", &Self::INIT_POW)
+            .field("limit_pow", &Self::LIMIT_POW)
+            .field("pow", &self.pow)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConstBackOff;
+    use crate::BackOffConfig;
+
+    #[test]
+    fn spin_full() {
+        const INIT: u32 = BackOffConfig::DEFAULT_INIT_POW;
+        const LIMIT: u32 = BackOffConfig::DEFAULT_SPIN_LIMIT_POW;
+
+        let mut backoff = ConstBackOff::<INIT, LIMIT>::new();
+        let mut steps = 1;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, LIMIT);
+        assert_eq!(backoff.steps(), 1 << LIMIT);
+
+        backoff.reset();
+        assert!(!backoff.advise_yield());
+        assert_eq!(backoff.steps(), 1 << INIT);
+        assert_eq!(ConstBackOff::<INIT, LIMIT>::default().steps(), 1 << INIT);
+    }
+
+    #[test]
+    fn clamped_pows() {
+        let backoff = ConstBackOff::<10, 4>::new();
+        assert!(backoff.advise_yield());
+        assert_eq!(backoff.steps(), 16);
+
+        let backoff = ConstBackOff::<40, 40>::new();
+        assert_eq!(backoff.steps(), 1 << 31);
+    }
+}
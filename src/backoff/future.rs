@@ -0,0 +1,191 @@
+//! An executor-agnostic future for backing off inside of `async` tasks.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use super::{BackOff, BackOffStrategy};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// SnoozeFuture
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The future returned by [`BackOff::snooze_async`].
+///
+/// As long as spinning is advisable, the future spins inline and completes on
+/// its first poll.
+/// Otherwise, it wakes its own task and returns [`Pending`][Poll::Pending]
+/// once, which yields the task back to the executor instead of blocking the
+/// executor's thread.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct SnoozeFuture<'a, S: BackOffStrategy> {
+    backoff: &'a mut BackOff<S>,
+    yielded: bool,
+}
+
+/********** impl Future ***************************************************************************/
+
+impl<S: BackOffStrategy> Future for SnoozeFuture<'_, S> {
+    type Output = ();
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if this.yielded {
+            return Poll::Ready(());
+        }
+
+        if !this.backoff.strategy.advise_yield(this.backoff.attempt) {
+            this.backoff.spin();
+            return Poll::Ready(());
+        }
+
+        this.yielded = true;
+        this.backoff.yields = this.backoff.yields.saturating_add(1);
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+/********** impl inherent *************************************************************************/
+
+impl<S: BackOffStrategy> BackOff<S> {
+    /// Backs off inside of an `async` task.
+    ///
+    /// While spinning is advisable, the returned future spins inline like
+    /// [`spin`][BackOff::spin] and completes immediately.
+    /// Once [`advise_yield`][BackOff::advise_yield] returns `true`, the future
+    /// instead yields to the executor once, by waking its own task and
+    /// returning [`Pending`][Poll::Pending], so that other tasks can make
+    /// progress on the same thread.
+    ///
+    /// The future does not depend on any particular executor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// use conquer_util::BackOff;
+    ///
+    /// async fn wait_for(flag: &AtomicBool) {
+    ///     let mut backoff = BackOff::new();
+    ///     while !flag.load(Ordering::Acquire) {
+    ///         backoff.snooze_async().await;
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn snooze_async(&mut self) -> SnoozeFuture<'_, S> {
+        SnoozeFuture { backoff: self, yielded: false }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use core::task::{Context, Poll, Waker};
+
+    use std::boxed::Box;
+    use std::sync::Arc;
+    use std::task::Wake;
+    use std::vec::Vec;
+
+    use crate::BackOff;
+
+    /// A waker counting its wake-ups.
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// A minimal single-threaded executor, which round-robins all tasks and
+    /// re-polls only those that have been woken.
+    fn run(tasks: Vec<Pin<Box<dyn Future<Output = ()>>>>) -> usize {
+        let mut tasks: Vec<_> = tasks
+            .into_iter()
+            .map(|task| (task, Arc::new(CountingWaker::default()), true))
+            .collect();
+        let mut pending = 0;
+
+        while !tasks.is_empty() {
+            let mut i = 0;
+            while i < tasks.len() {
+                let (task, waker, woken) = &mut tasks[i];
+                if !*woken {
+                    i += 1;
+                    continue;
+                }
+
+                let before = waker.0.load(Ordering::Relaxed);
+                let cx_waker = Waker::from(Arc::clone(waker));
+                match task.as_mut().poll(&mut Context::from_waker(&cx_waker)) {
+                    Poll::Ready(()) => {
+                        drop(tasks.remove(i));
+                    }
+                    Poll::Pending => {
+                        pending += 1;
+                        *woken = waker.0.load(Ordering::Relaxed) > before;
+                        assert!(*woken, "task must wake itself");
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        pending
+    }
+
+    #[test]
+    fn spin_inline_then_yield() {
+        let mut backoff = BackOff::new();
+        let waker = Waker::from(Arc::new(CountingWaker::default()));
+        let mut cx = Context::from_waker(&waker);
+
+        while !backoff.advise_yield() {
+            assert_eq!(Pin::new(&mut backoff.snooze_async()).poll(&mut cx), Poll::Ready(()));
+        }
+
+        let mut future = backoff.snooze_async();
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Pending);
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    fn interleave_tasks() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let flag = Rc::new(Cell::new(false));
+        let waiter = {
+            let flag = Rc::clone(&flag);
+            async move {
+                let mut backoff = BackOff::new();
+                while !flag.get() {
+                    backoff.snooze_async().await;
+                }
+            }
+        };
+        let setter = {
+            let flag = Rc::clone(&flag);
+            async move { flag.set(true) }
+        };
+
+        // the waiter would block the executor forever, if it did not yield
+        let pending = run(std::vec![Box::pin(waiter), Box::pin(setter)]);
+        assert_eq!(pending, 1);
+    }
+}
@@ -0,0 +1,359 @@
+//! Hardware-assisted waiting through dedicated CPU instructions, which allow
+//! the CPU to enter a low-power state instead of busy spinning.
+//!
+//! On x86_64 CPUs supporting the WAITPKG extension (detected at runtime via
+//! `cpuid`), `umonitor`/`umwait` are used for waiting on a memory location and
+//! `tpause` for timed pauses.
+//! On AArch64, the exclusive monitor is armed through a load-exclusive and the
+//! CPU waits for the resulting event through `wfe`.
+//! On all other CPUs, the regular back-off spin loop is used instead.
+
+#[cfg(target_arch = "aarch64")]
+use core::arch::asm;
+use core::sync::atomic::{
+    AtomicI16, AtomicI32, AtomicI8, AtomicIsize, AtomicPtr, AtomicU16, AtomicU32, AtomicU8,
+    AtomicUsize, Ordering,
+};
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicI64, AtomicU64};
+
+use super::BackOff;
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// AtomicWait
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An atomic type, whose memory location can be monitored for changes by
+/// [`wait_on_address_change`][BackOff::wait_on_address_change].
+///
+/// This trait is sealed and implemented for all integer atomic types as well
+/// as for [`AtomicPtr`].
+/// The 64-bit atomic types only implement this trait on platforms supporting
+/// 64-bit atomic operations.
+pub trait AtomicWait: sealed::Sealed {
+    /// The type of the value stored in the atomic type.
+    type Value: Copy + Eq;
+
+    #[doc(hidden)]
+    fn load_acquire(&self) -> Self::Value;
+
+    #[doc(hidden)]
+    #[cfg(target_arch = "aarch64")]
+    fn load_exclusive(&self) -> Self::Value;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_atomic_wait {
+    ($($(#[$attr:meta])* impl$(<$param:ident>)? for $atomic:ty => $value:ty, $raw:ty, $ldaxr:literal;)*) => {
+        $(
+            $(#[$attr])*
+            impl$(<$param>)? sealed::Sealed for $atomic {}
+
+            $(#[$attr])*
+            impl$(<$param>)? AtomicWait for $atomic {
+                type Value = $value;
+
+                #[inline]
+                fn load_acquire(&self) -> Self::Value {
+                    self.load(Ordering::Acquire)
+                }
+
+                #[cfg(target_arch = "aarch64")]
+                #[inline]
+                fn load_exclusive(&self) -> Self::Value {
+                    let raw: $raw;
+                    // SAFETY: the address is valid and suitably aligned for
+                    // the atomic type and the load has acquire semantics
+                    unsafe {
+                        asm!(
+                            $ldaxr,
+                            raw = out(reg) raw,
+                            addr = in(reg) self as *const Self,
+                            options(nostack, preserves_flags),
+                        );
+                    }
+
+                    raw as Self::Value
+                }
+            }
+        )*
+    };
+}
+
+impl_atomic_wait! {
+    impl for AtomicI8 => i8, u32, "ldaxrb {raw:w}, [{addr}]";
+    impl for AtomicI16 => i16, u32, "ldaxrh {raw:w}, [{addr}]";
+    impl for AtomicI32 => i32, u32, "ldaxr {raw:w}, [{addr}]";
+    #[cfg(target_has_atomic = "64")]
+    impl for AtomicI64 => i64, u64, "ldaxr {raw:x}, [{addr}]";
+    impl for AtomicIsize => isize, u64, "ldaxr {raw:x}, [{addr}]";
+    impl for AtomicU8 => u8, u32, "ldaxrb {raw:w}, [{addr}]";
+    impl for AtomicU16 => u16, u32, "ldaxrh {raw:w}, [{addr}]";
+    impl for AtomicU32 => u32, u32, "ldaxr {raw:w}, [{addr}]";
+    #[cfg(target_has_atomic = "64")]
+    impl for AtomicU64 => u64, u64, "ldaxr {raw:x}, [{addr}]";
+    impl for AtomicUsize => usize, u64, "ldaxr {raw:x}, [{addr}]";
+    impl<T> for AtomicPtr<T> => *mut T, u64, "ldaxr {raw:x}, [{addr}]";
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// platform dispatch
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Returns `true` if hardware-assisted waiting is supported by the CPU.
+#[inline]
+pub(super) fn is_supported() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        waitpkg::is_supported()
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        true
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+/// Waits until the value of `atomic` differs from `current` and returns the
+/// new value.
+#[inline]
+pub(super) fn wait_on_address_change<A: AtomicWait>(atomic: &A, current: A::Value) -> A::Value {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if waitpkg::is_supported() {
+            return waitpkg::wait_on_address_change(atomic, current);
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        return wfe::wait_on_address_change(atomic, current);
+    }
+
+    #[allow(unreachable_code)]
+    spin_on_address_change(atomic, current)
+}
+
+/// Pauses until the TSC has advanced by *at least* `cycles` ticks.
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub(super) fn pause_for_cycles(cycles: u64) {
+    if waitpkg::is_supported() {
+        waitpkg::pause_for_cycles(cycles);
+    } else {
+        super::tsc::spin_for_cycles(cycles);
+    }
+}
+
+/// The fallback for CPUs without hardware support, which spins (or yields, if
+/// the `std` feature is enabled) with exponential back-off.
+#[inline]
+fn spin_on_address_change<A: AtomicWait>(atomic: &A, current: A::Value) -> A::Value {
+    let mut backoff = BackOff::new();
+    loop {
+        let value = atomic.load_acquire();
+        if value != current {
+            return value;
+        }
+
+        backoff.wait();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// x86_64 (WAITPKG)
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(target_arch = "x86_64")]
+mod waitpkg {
+    use core::arch::{asm, x86_64};
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    use super::super::tsc;
+    use super::AtomicWait;
+
+    /// The marker values for the cached result of the CPUID check.
+    const UNKNOWN: u8 = 0;
+    const UNSUPPORTED: u8 = 1;
+    const SUPPORTED: u8 = 2;
+
+    /// The control value selecting the C0.1 power state, which saves less
+    /// energy than C0.2 but offers faster wake-up times.
+    const C0_1: u32 = 1;
+    /// The upper bound for a single `umwait`, after which the monitored value
+    /// is re-checked (e.g. in case of missed wake-ups).
+    const MAX_WAIT_CYCLES: u64 = 100_000;
+
+    /// The cached result of the CPUID check.
+    static WAITPKG: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+    /// Returns `true` if the CPU supports the WAITPKG extension.
+    #[inline]
+    pub(super) fn is_supported() -> bool {
+        match WAITPKG.load(Ordering::Relaxed) {
+            UNKNOWN => detect(),
+            state => state == SUPPORTED,
+        }
+    }
+
+    /// Checks the CPUID flag for the WAITPKG extension and caches the result.
+    fn detect() -> bool {
+        const EXT_FEATURES_LEAF: u32 = 7;
+        const WAITPKG_BIT: u32 = 1 << 5;
+
+        // SAFETY: the `cpuid` instruction is available on all x86_64 CPUs
+        #[allow(unused_unsafe)]
+        let supported = unsafe {
+            x86_64::__cpuid(0).eax >= EXT_FEATURES_LEAF
+                && x86_64::__cpuid_count(EXT_FEATURES_LEAF, 0).ecx & WAITPKG_BIT != 0
+        };
+
+        WAITPKG.store(if supported { SUPPORTED } else { UNSUPPORTED }, Ordering::Relaxed);
+        supported
+    }
+
+    #[inline]
+    pub(super) fn wait_on_address_change<A: AtomicWait>(atomic: &A, current: A::Value) -> A::Value {
+        loop {
+            // SAFETY: WAITPKG support has been checked by the caller
+            unsafe { umonitor(atomic as *const A as *const u8) };
+            // the value must be re-checked after arming the monitor, since
+            // any previous write would go unnoticed otherwise
+            let value = atomic.load_acquire();
+            if value != current {
+                return value;
+            }
+
+            // SAFETY: WAITPKG support has been checked by the caller
+            unsafe { umwait(tsc::rdtsc().wrapping_add(MAX_WAIT_CYCLES)) };
+        }
+    }
+
+    #[inline]
+    pub(super) fn pause_for_cycles(cycles: u64) {
+        let deadline = tsc::rdtsc().saturating_add(cycles);
+        // `tpause` may wake up early, e.g. due to interrupts or an OS imposed
+        // time limit
+        while tsc::rdtsc() < deadline {
+            // SAFETY: WAITPKG support has been checked by the caller
+            unsafe { tpause(deadline) };
+        }
+    }
+
+    /// Arms the address monitor for the cache line containing `addr`.
+    #[inline]
+    unsafe fn umonitor(addr: *const u8) {
+        asm!("umonitor {}", in(reg) addr, options(readonly, nostack, preserves_flags));
+    }
+
+    /// Waits until either the monitored cache line is written to or the TSC
+    /// reaches the `deadline`.
+    #[inline]
+    unsafe fn umwait(deadline: u64) {
+        asm!(
+            "umwait {:e}",
+            in(reg) C0_1,
+            in("edx") (deadline >> 32) as u32,
+            in("eax") deadline as u32,
+            options(nomem, nostack),
+        );
+    }
+
+    /// Waits until the TSC reaches the `deadline`.
+    #[inline]
+    unsafe fn tpause(deadline: u64) {
+        asm!(
+            "tpause {:e}",
+            in(reg) C0_1,
+            in("edx") (deadline >> 32) as u32,
+            in("eax") deadline as u32,
+            options(nomem, nostack),
+        );
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// aarch64 (WFE)
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(target_arch = "aarch64")]
+mod wfe {
+    use core::arch::asm;
+
+    use super::AtomicWait;
+
+    #[inline]
+    pub(super) fn wait_on_address_change<A: AtomicWait>(atomic: &A, current: A::Value) -> A::Value {
+        loop {
+            // the load-exclusive arms the exclusive monitor, any subsequent
+            // write to the location clears it and generates a wake-up event
+            let value = atomic.load_exclusive();
+            if value != current {
+                // SAFETY: clearing the exclusive monitor has no other effects
+                unsafe { asm!("clrex", options(nomem, nostack, preserves_flags)) };
+                return value;
+            }
+
+            // SAFETY: `wfe` returns at the latest with the next event, which
+            // includes the periodic event stream on Linux
+            unsafe { asm!("wfe", options(nomem, nostack, preserves_flags)) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::sync::atomic::{AtomicI16, AtomicU32, Ordering};
+
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn wait_on_address_change() {
+        let atomic = Arc::new(AtomicU32::new(0));
+        let handle = {
+            let atomic = Arc::clone(&atomic);
+            thread::spawn(move || {
+                thread::yield_now();
+                atomic.store(1, Ordering::Release);
+            })
+        };
+
+        assert_eq!(super::wait_on_address_change(&*atomic, 0), 1);
+        handle.join().unwrap();
+        assert_eq!(super::wait_on_address_change(&*atomic, 0), 1);
+    }
+
+    #[test]
+    fn spin_on_address_change() {
+        let atomic = Arc::new(AtomicI16::new(-1));
+        let handle = {
+            let atomic = Arc::clone(&atomic);
+            thread::spawn(move || {
+                thread::yield_now();
+                atomic.store(1, Ordering::Release);
+            })
+        };
+
+        assert_eq!(super::spin_on_address_change(&*atomic, -1), 1);
+        handle.join().unwrap();
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn pause_for_cycles() {
+        let start = super::super::tsc::rdtsc();
+        super::pause_for_cycles(10_000);
+        assert!(super::super::tsc::rdtsc().wrapping_sub(start) >= 10_000);
+    }
+}
@@ -0,0 +1,1996 @@
+//! Convenience type for ergonomically pursuing an exponential back-off busy
+//! waiting strategy in order to reduce contention on shared memory and caches
+//! in a concurrent environment.
+
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+#[deny(unsafe_code)]
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use core::fmt;
+use core::sync::atomic;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+mod calibrate;
+mod cas;
+mod clock;
+mod fixed;
+#[cfg(feature = "async")]
+mod future;
+#[cfg(feature = "hw-wait")]
+mod hw;
+#[cfg(feature = "random")]
+mod rng;
+#[cfg(target_has_atomic = "32")]
+mod shared;
+#[cfg(feature = "std")]
+mod sleep;
+#[cfg(feature = "stats")]
+mod stats;
+mod strategy;
+#[cfg(target_arch = "x86_64")]
+mod tsc;
+
+pub use self::cas::AtomicRetry;
+pub use self::clock::Clock;
+#[cfg(feature = "std")]
+pub use self::clock::StdClock;
+pub use self::fixed::ConstBackOff;
+#[cfg(feature = "async")]
+pub use self::future::SnoozeFuture;
+#[cfg(feature = "hw-wait")]
+pub use self::hw::AtomicWait;
+#[cfg(feature = "rand")]
+pub use self::rng::RandRng;
+#[cfg(feature = "random")]
+pub use self::rng::{BackOffRng, WyRand};
+#[cfg(target_has_atomic = "32")]
+pub use self::shared::{AtomicBackOff, AtomicStrategy};
+#[cfg(feature = "stats")]
+pub use self::stats::BackOffStats;
+pub use self::strategy::{Adaptive, BackOffStrategy, DefaultStrategy};
+#[cfg(feature = "random")]
+pub use self::strategy::{Jitter, RandomStrategy};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BackOff
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A type for exponential back-off in tight loops.
+///
+/// In concurrent environments it can often be beneficial to back off from
+/// accessing shared variables in loops in order to reduce contention and
+/// improve performance for all participating threads by spinning for a short
+/// amount of time.
+///
+/// All methods that advance the back-off require unique (`&mut`) access, so
+/// that no runtime borrow checking is required on the hot path.
+///
+/// The number of steps spun in each round is determined by a
+/// [`BackOffStrategy`], which defaults to the [`DefaultStrategy`] that is used
+/// by all of the built-in constructors.
+/// Custom strategies can be used through
+/// [`with_strategy`][BackOff::with_strategy].
+#[derive(Clone)]
+pub struct BackOff<S = DefaultStrategy> {
+    strategy: S,
+    attempt: u32,
+    rounds: u32,
+    yields: u32,
+    #[cfg(feature = "std")]
+    sleeps: u32,
+    #[cfg(feature = "stats")]
+    stats: stats::Counters,
+}
+
+/********** impl Default **************************************************************************/
+
+impl<S: BackOffStrategy + Default> Default for BackOff<S> {
+    #[inline]
+    fn default() -> Self {
+        Self::with_strategy(S::default())
+    }
+}
+
+/********** impl inherent *************************************************************************/
+
+impl BackOff {
+    /// Creates a new [`BackOff`] instance with a fixed exponential back-off
+    /// strategy.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_config(BackOffConfig::new())
+    }
+
+    /// Creates a new [`BackOff`] instance with a fixed exponential back-off
+    /// strategy using the given `config`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use conquer_util::{BackOff, BackOffConfig};
+    ///
+    /// static CONFIG: BackOffConfig = BackOffConfig::new().init_pow(3).spin_limit_pow(10);
+    ///
+    /// let mut backoff = BackOff::with_config(CONFIG);
+    /// while !backoff.advise_yield() {
+    ///     backoff.spin();
+    /// }
+    /// ```
+    #[inline]
+    pub const fn with_config(config: BackOffConfig) -> Self {
+        Self::with_strategy(DefaultStrategy::constant(config))
+    }
+
+    /// Spin once.
+    ///
+    /// This is a convenience wrapper for
+    /// [`spin_loop_hint`][core::sync::atomic::spin_loop_hint], but will never
+    /// compile to only a nop on platforms, that don't offer a `wait`-like CPU
+    /// instruction, but will instead result in an empty function call.
+    #[inline(never)]
+    pub fn spin_once() {
+        atomic::spin_loop_hint();
+    }
+
+    /// Spins until the CPU's time stamp counter (TSC) has advanced by *at
+    /// least* `cycles` ticks.
+    ///
+    /// Reading the TSC is much cheaper than querying the OS clock, which makes
+    /// this function suitable for very short waits.
+    /// Note that the TSC only ticks at a constant rate on CPUs with an
+    /// *invariant* TSC, which all recent x86_64 CPUs provide.
+    #[cfg(target_arch = "x86_64")]
+    #[inline]
+    pub fn spin_for_cycles(cycles: u64) {
+        tsc::spin_for_cycles(cycles);
+    }
+
+    /// Spins *at least* for the specified `dur` as measured by the given
+    /// `clock`.
+    ///
+    /// Unlike [`spin_for`][BackOff::spin_for], this function is also
+    /// available in `#![no_std]` environments.
+    #[inline]
+    pub fn spin_for_with_clock<C: Clock>(clock: &C, dur: Duration) {
+        let end = clock.add(clock.now(), dur);
+        while clock.now() < end {
+            Self::spin_once();
+        }
+    }
+}
+
+impl<S> BackOff<S> {
+    /// Creates a new [`BackOff`] instance with the given back-off `strategy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use conquer_util::{BackOff, BackOffStrategy};
+    ///
+    /// /// A strategy spinning for a linearly increasing number of steps.
+    /// struct Linear;
+    ///
+    /// impl BackOffStrategy for Linear {
+    ///     fn spin_steps(&mut self, attempt: u32) -> u32 {
+    ///         (attempt + 1) * 16
+    ///     }
+    ///
+    ///     fn advise_yield(&self, attempt: u32) -> bool {
+    ///         attempt >= 8
+    ///     }
+    /// }
+    ///
+    /// let mut backoff = BackOff::with_strategy(Linear);
+    /// while !backoff.advise_yield() {
+    ///     backoff.spin();
+    /// }
+    /// ```
+    #[inline]
+    pub const fn with_strategy(strategy: S) -> Self {
+        Self {
+            strategy,
+            attempt: 0,
+            rounds: 0,
+            yields: 0,
+            #[cfg(feature = "std")]
+            sleeps: 0,
+            #[cfg(feature = "stats")]
+            stats: stats::Counters::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying back-off strategy.
+    #[inline]
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+}
+
+impl<S: BackOffStrategy> BackOff<S> {
+    /// Resets the [`BackOff`] instance to its initial state.
+    #[inline]
+    pub fn reset(&mut self) {
+        #[cfg(feature = "stats")]
+        self.stats.record_reset();
+        self.attempt = 0;
+        self.rounds = 0;
+        self.yields = 0;
+        #[cfg(feature = "std")]
+        {
+            self.sleeps = 0;
+        }
+        self.strategy.reset();
+    }
+
+    /// Spins for a bounded number of steps
+    ///
+    /// On CPUs that support such instructions, in each step the processor will
+    /// be instructed to deliberately slow down, e.g. using the `pause`
+    /// instruction on x86, which can also save energy.
+    ///
+    /// Each invocation of this method exponentially increases the number of
+    /// spin cycles until a point at which further spinning is no longer
+    /// advisable and other strategies, such as yielding the current thread to
+    /// the OS, should be preferred.
+    /// From this point on, the number of spin cycles remains constant with each
+    /// further invocation of [`spin`][BackOff::spin].
+    ///
+    /// Whether this point has been reached can be determined through the
+    /// [`advise_yield`][BackOff::advise_yield] method.
+    #[inline]
+    pub fn spin(&mut self) {
+        let steps = self.strategy.spin_steps(self.attempt);
+        #[cfg(feature = "stats")]
+        self.stats.record_spin(steps);
+        self.rounds = self.rounds.saturating_add(1);
+        if !self.strategy.advise_yield(self.attempt) {
+            self.attempt = self.attempt.saturating_add(1);
+        }
+
+        for _ in 0..steps {
+            BackOff::spin_once();
+        }
+    }
+
+    /// Spins for a bounded number of steps like [`spin`][BackOff::spin], unless
+    /// the back-off budget has been exhausted.
+    ///
+    /// The budget is determined by the strategy's
+    /// [`max_attempts`][BackOffStrategy::max_attempts] (see also
+    /// [`BackOffConfig::max_attempts`]) and is replenished when the
+    /// [`BackOff`] is reset.
+    ///
+    /// # Errors
+    ///
+    /// Fails without spinning, if the maximum number of back-off rounds has
+    /// already been spun since the last reset.
+    /// Callers can then fall back to e.g. acquiring a lock or report the
+    /// failure instead of spinning indefinitely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use conquer_util::{BackOff, BackOffConfig};
+    ///
+    /// let mut backoff = BackOff::with_config(BackOffConfig::new().max_attempts(3));
+    /// assert!(backoff.try_spin().is_ok());
+    /// assert!(backoff.try_spin().is_ok());
+    /// assert!(backoff.try_spin().is_ok());
+    /// assert!(backoff.try_spin().is_err());
+    ///
+    /// backoff.reset();
+    /// assert!(backoff.try_spin().is_ok());
+    /// ```
+    #[inline]
+    pub fn try_spin(&mut self) -> Result<(), BackOffExhausted> {
+        if self.is_exhausted() {
+            Err(BackOffExhausted(()))
+        } else {
+            self.spin();
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if the back-off budget has been exhausted, i.e. further
+    /// calls to [`try_spin`][BackOff::try_spin] will fail until the
+    /// [`BackOff`] is reset.
+    #[inline]
+    pub fn is_exhausted(&self) -> bool {
+        match self.strategy.max_attempts() {
+            Some(max) => self.rounds >= max,
+            None => false,
+        }
+    }
+
+    /// Reports a successful operation to the back-off strategy and resets the
+    /// [`BackOff`] instance.
+    ///
+    /// Strategies such as [`Adaptive`] use this information to adjust their
+    /// behaviour across resets.
+    #[inline]
+    pub fn success(&mut self) {
+        self.strategy.on_success();
+        self.reset();
+    }
+
+    /// Reports a failed (contended) operation to the back-off strategy.
+    ///
+    /// Strategies such as [`Adaptive`] use this information to adjust their
+    /// behaviour across resets.
+    #[inline]
+    pub fn failure(&mut self) {
+        self.strategy.on_failure();
+    }
+
+    /// Returns `true` if further spinning is not advisable and other means such
+    /// as voluntarily yielding the current thread could be more efficient.
+    ///
+    /// # Examples
+    ///
+    /// Back-off exponentially until it is no longer advisable.
+    ///
+    /// ```
+    /// use conquer_util::BackOff;
+    ///
+    /// let mut backoff = BackOff::new();
+    /// while !backoff.advise_yield() {
+    ///     backoff.spin();
+    /// }
+    /// ```
+    ///
+    /// Repedeatly check a condition and either back-off exponentially or yield
+    /// the current thread, if the condition is not yet met.
+    ///
+    /// ```
+    /// use conquer_util::BackOff;
+    ///
+    /// # let cond = true;
+    ///
+    /// let mut backoff = BackOff::new();
+    /// while !cond {
+    ///     if backoff.advise_yield() {
+    ///         std::thread::yield_now();
+    ///     } else {
+    ///         backoff.spin();
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Notes
+    ///
+    /// On an Intel(R) i5 with 2.60 GHz a full back-off cycle has been measured
+    /// to take approximately 750 nanoseconds
+    #[inline]
+    pub fn advise_yield(&self) -> bool {
+        let advise = self.strategy.advise_yield(self.attempt);
+        #[cfg(feature = "stats")]
+        {
+            if advise {
+                self.stats.record_advised_yield();
+            }
+        }
+
+        advise
+    }
+
+    /// Returns a snapshot of the statistics recorded by this [`BackOff`]
+    /// instance.
+    ///
+    /// Clones of an instance start out with fresh statistics.
+    #[cfg(feature = "stats")]
+    #[inline]
+    pub fn stats(&self) -> BackOffStats {
+        self.stats.snapshot()
+    }
+
+    /// Backs off until the given condition `cond` is met.
+    ///
+    /// If the `std` feature is enabled, the current thread is yielded instead
+    /// of spinning once further spinning is no longer advisable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// use conquer_util::BackOff;
+    ///
+    /// let flag = AtomicBool::new(true);
+    /// BackOff::new().spin_until(|| flag.load(Ordering::Acquire));
+    /// ```
+    #[inline]
+    pub fn spin_until(&mut self, mut cond: impl FnMut() -> bool) {
+        while !cond() {
+            self.wait();
+        }
+    }
+
+    /// Backs off as long as the given condition `cond` is met.
+    ///
+    /// See [`spin_until`][BackOff::spin_until] for details.
+    #[inline]
+    pub fn spin_while(&mut self, mut cond: impl FnMut() -> bool) {
+        self.spin_until(|| !cond())
+    }
+
+    /// Backs off until the given condition `cond` is met, but at most
+    /// `max_attempts` times.
+    ///
+    /// # Errors
+    ///
+    /// Fails, if the condition is still not met after backing off
+    /// `max_attempts` times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use conquer_util::BackOff;
+    ///
+    /// let mut backoff = BackOff::new();
+    /// assert!(backoff.spin_until_bounded(8, || false).is_err());
+    /// ```
+    #[inline]
+    pub fn spin_until_bounded(
+        &mut self,
+        max_attempts: u32,
+        mut cond: impl FnMut() -> bool,
+    ) -> Result<(), TimeoutError> {
+        for _ in 0..max_attempts {
+            if cond() {
+                return Ok(());
+            }
+
+            self.wait();
+        }
+
+        if cond() {
+            Ok(())
+        } else {
+            Err(TimeoutError(()))
+        }
+    }
+
+    /// Backs off as long as the given condition `cond` is met, but at most
+    /// `max_attempts` times.
+    ///
+    /// See [`spin_until_bounded`][BackOff::spin_until_bounded] for details.
+    ///
+    /// # Errors
+    ///
+    /// Fails, if the condition is still met after backing off `max_attempts`
+    /// times.
+    #[inline]
+    pub fn spin_while_bounded(
+        &mut self,
+        max_attempts: u32,
+        mut cond: impl FnMut() -> bool,
+    ) -> Result<(), TimeoutError> {
+        self.spin_until_bounded(max_attempts, || !cond())
+    }
+
+    /// Backs off until the given condition `cond` is met or the `deadline`
+    /// as measured by the given `clock` has passed.
+    ///
+    /// See [`spin_until`][BackOff::spin_until] for details.
+    /// Unlike [`spin_until_deadline`][BackOff::spin_until_deadline], this
+    /// method is also available in `#![no_std]` environments.
+    ///
+    /// # Errors
+    ///
+    /// Fails, if the condition is still not met once the `deadline` has
+    /// passed.
+    #[inline]
+    pub fn spin_until_deadline_with_clock<C: Clock>(
+        &mut self,
+        clock: &C,
+        deadline: C::Instant,
+        mut cond: impl FnMut() -> bool,
+    ) -> Result<(), TimeoutError> {
+        while !cond() {
+            if clock.now() >= deadline {
+                return Err(TimeoutError(()));
+            }
+
+            self.wait();
+        }
+
+        Ok(())
+    }
+
+    /// Backs off as long as the given condition `cond` is met or until the
+    /// `deadline` as measured by the given `clock` has passed.
+    ///
+    /// See [`spin_until_deadline_with_clock`][BackOff::spin_until_deadline_with_clock]
+    /// for details.
+    ///
+    /// # Errors
+    ///
+    /// Fails, if the condition is still met once the `deadline` has passed.
+    #[inline]
+    pub fn spin_while_deadline_with_clock<C: Clock>(
+        &mut self,
+        clock: &C,
+        deadline: C::Instant,
+        mut cond: impl FnMut() -> bool,
+    ) -> Result<(), TimeoutError> {
+        self.spin_until_deadline_with_clock(clock, deadline, || !cond())
+    }
+
+    /// Performs a single back-off step of a condition-driven spin loop.
+    #[inline]
+    fn wait(&mut self) {
+        #[cfg(feature = "std")]
+        {
+            if self.strategy.advise_yield(self.attempt) {
+                BackOff::yield_now();
+                return;
+            }
+        }
+
+        self.spin();
+    }
+}
+
+#[cfg(feature = "hw-wait")]
+impl BackOff {
+    /// Returns `true` if the CPU supports dedicated instructions for waiting
+    /// in a low-power state, which are used by
+    /// [`wait_on_address_change`][BackOff::wait_on_address_change] and
+    /// [`pause_for_cycles`][BackOff::pause_for_cycles].
+    ///
+    /// These are `umonitor`/`umwait` and `tpause` on x86_64 CPUs with the
+    /// WAITPKG extension, which is detected once at runtime, and `wfe` on
+    /// AArch64.
+    #[inline]
+    pub fn hw_wait_supported() -> bool {
+        hw::is_supported()
+    }
+
+    /// Waits until the value of `atomic` differs from `current` and returns
+    /// the new value, which is loaded with [`Acquire`][atomic::Ordering::Acquire]
+    /// ordering.
+    ///
+    /// If [supported][BackOff::hw_wait_supported], the CPU is instructed to
+    /// wait in a low-power state until the cache line containing `atomic` is
+    /// written to.
+    /// Otherwise, this function falls back to a regular back-off spin loop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// use conquer_util::BackOff;
+    ///
+    /// let flag = Arc::new(AtomicU32::new(0));
+    /// let handle = {
+    ///     let flag = Arc::clone(&flag);
+    ///     thread::spawn(move || flag.store(1, Ordering::Release))
+    /// };
+    ///
+    /// assert_eq!(BackOff::wait_on_address_change(&*flag, 0), 1);
+    /// handle.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn wait_on_address_change<A: AtomicWait>(atomic: &A, current: A::Value) -> A::Value {
+        hw::wait_on_address_change(atomic, current)
+    }
+
+    /// Pauses until the CPU's time stamp counter (TSC) has advanced by *at
+    /// least* `cycles` ticks.
+    ///
+    /// If the CPU supports the WAITPKG extension, the `tpause` instruction is
+    /// used to wait in a low-power state.
+    /// Otherwise, this function falls back to
+    /// [`spin_for_cycles`][BackOff::spin_for_cycles].
+    #[cfg(target_arch = "x86_64")]
+    #[inline]
+    pub fn pause_for_cycles(cycles: u64) {
+        hw::pause_for_cycles(cycles);
+    }
+
+    /// Pauses *at least* for the specified `dur`.
+    ///
+    /// On x86_64 CPUs with the WAITPKG extension and an invariant TSC, the
+    /// `tpause` instruction is used to wait in a low-power state, once the TSC
+    /// has been calibrated as described for [`spin_for`][BackOff::spin_for].
+    /// Otherwise, this function falls back to [`spin_for`][BackOff::spin_for].
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn pause_for(dur: Duration) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            let start = Instant::now();
+            if let Some(freq) = tsc::frequency_within(dur) {
+                let rem = dur.checked_sub(start.elapsed()).unwrap_or_default();
+                hw::pause_for_cycles(tsc::cycles_for(rem, freq));
+                return;
+            }
+        }
+
+        Self::spin_for(dur);
+    }
+}
+
+#[cfg(feature = "random")]
+impl BackOff {
+    /// Creates a new [`BackOff`] instance with a randomized exponential
+    /// back-off strategy.
+    pub fn random() -> Self {
+        Self::random_with_config(BackOffConfig::new())
+    }
+
+    /// Creates a new [`BackOff`] instance with a randomized exponential
+    /// back-off strategy using the given `seed` value.
+    pub fn random_with_seed(seed: u64) -> Self {
+        Self::random_with_seed_and_config(seed, BackOffConfig::new())
+    }
+
+    /// Creates a new [`BackOff`] instance with a randomized exponential
+    /// back-off strategy using the given `config`.
+    pub fn random_with_config(config: BackOffConfig) -> Self {
+        Self::with_strategy(DefaultStrategy::random(config))
+    }
+
+    /// Creates a new [`BackOff`] instance with a randomized exponential
+    /// back-off strategy using the given `seed` value and `config`.
+    pub fn random_with_seed_and_config(seed: u64, config: BackOffConfig) -> Self {
+        Self::with_strategy(DefaultStrategy::random_with_seed(seed, config))
+    }
+}
+
+#[cfg(feature = "random")]
+impl<R: BackOffRng> BackOff<RandomStrategy<R>> {
+    /// Creates a new [`BackOff`] instance with a randomized exponential
+    /// back-off strategy drawing its random numbers from the given `rng`.
+    ///
+    /// See [`RandomStrategy`] for details.
+    #[inline]
+    pub fn random_with_rng(rng: R) -> Self {
+        Self::random_with_rng_and_config(rng, BackOffConfig::new())
+    }
+
+    /// Creates a new [`BackOff`] instance with a randomized exponential
+    /// back-off strategy drawing its random numbers from the given `rng` and
+    /// using the given `config`.
+    #[inline]
+    pub fn random_with_rng_and_config(rng: R, config: BackOffConfig) -> Self {
+        Self::with_strategy(RandomStrategy::new(rng, config))
+    }
+}
+
+impl BackOff<Adaptive> {
+    /// Creates a new [`BackOff`] instance with an [`Adaptive`] fixed
+    /// exponential back-off strategy.
+    #[inline]
+    pub const fn adaptive() -> Self {
+        Self::adaptive_with_config(BackOffConfig::new())
+    }
+
+    /// Creates a new [`BackOff`] instance with an [`Adaptive`] fixed
+    /// exponential back-off strategy using the given `config`.
+    #[inline]
+    pub const fn adaptive_with_config(config: BackOffConfig) -> Self {
+        Self::with_strategy(Adaptive::new(DefaultStrategy::constant(config)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl BackOff {
+    /// Spins *at least* for the specified `dur`.
+    ///
+    /// If a very short duration is specified, this function may spin for a
+    /// longer, platform-specific minimum time.
+    ///
+    /// On x86_64 CPUs with an invariant time stamp counter (TSC), the TSC is
+    /// used as a low-overhead clock, after its frequency has been calibrated
+    /// once against the OS clock.
+    /// Otherwise, the OS clock is queried in each iteration.
+    ///
+    /// The calibration busy-waits for about 2 milliseconds, which counts
+    /// against `dur`, so it is only performed during sufficiently long spins
+    /// and shorter spins use the OS clock until then.
+    /// Call [`tsc_frequency`][BackOff::tsc_frequency] at start-up in order to
+    /// use the TSC right away.
+    pub fn spin_for(dur: Duration) {
+        let start = Instant::now();
+        #[cfg(target_arch = "x86_64")]
+        {
+            if let Some(freq) = tsc::frequency_within(dur) {
+                let rem = dur.checked_sub(start.elapsed()).unwrap_or_default();
+                tsc::spin_for_cycles(tsc::cycles_for(rem, freq));
+                return;
+            }
+        }
+
+        let end = start + dur;
+
+        while Instant::now() < end {
+            Self::spin_once();
+        }
+    }
+
+    /// Creates a new [`BackOff`] instance with a fixed exponential back-off
+    /// strategy that spins for approximately `min` in its first round and
+    /// advises yielding once a round takes approximately `max`.
+    ///
+    /// See [`BackOffConfig::from_durations`] for details.
+    ///
+    /// # Panics
+    ///
+    /// This function panics, if `min` is greater than `max`.
+    #[inline]
+    pub fn with_durations(min: Duration, max: Duration) -> Self {
+        Self::with_config(BackOffConfig::from_durations(min, max))
+    }
+
+    /// Creates a new [`BackOff`] instance with a fixed exponential back-off
+    /// strategy, whose spin limit is scaled to the number of CPUs usable by
+    /// the current process.
+    ///
+    /// On a single CPU, spinning is skipped entirely and yielding is advised
+    /// right away.
+    /// See [`BackOffConfig::scale_to_available_parallelism`] for details.
+    #[inline]
+    pub fn cpu_aware() -> Self {
+        Self::with_config(BackOffConfig::new().scale_to_available_parallelism())
+    }
+
+    /// Returns the calibrated frequency of the CPU's time stamp counter (TSC)
+    /// in Hz or `None`, if the CPU does not provide an invariant TSC.
+    ///
+    /// The frequency is measured only once during the first call to this
+    /// function or a sufficiently long [`spin_for`][BackOff::spin_for] and
+    /// cached afterwards.
+    /// The measurement busy-waits for about 2 milliseconds, so this function
+    /// should be called once at start-up, if short spins are to use the TSC
+    /// from the start.
+    #[cfg(target_arch = "x86_64")]
+    #[inline]
+    pub fn tsc_frequency() -> Option<u64> {
+        tsc::frequency()
+    }
+
+    /// Returns the (approximate) duration of a single spin step, i.e. a call
+    /// to [`spin_once`][BackOff::spin_once].
+    ///
+    /// The duration is measured only once during the first call to this or
+    /// any other function depending on it and cached afterwards.
+    /// It is measured with picosecond precision and rounded to the nearest
+    /// nanosecond, internally the unrounded value is used.
+    #[inline]
+    pub fn spin_once_cost() -> Duration {
+        calibrate::picos_to_duration(calibrate::spin_cost_picos())
+    }
+
+    /// Re-measures the duration of a single spin step and updates the cached
+    /// value, e.g. after the thread has been migrated to a different CPU.
+    ///
+    /// The returned duration is rounded like for
+    /// [`spin_once_cost`][BackOff::spin_once_cost].
+    #[inline]
+    pub fn calibrate() -> Duration {
+        calibrate::picos_to_duration(calibrate::calibrate())
+    }
+
+    /// Puts the current thread to sleep for *at least* the specified `dur` with
+    /// a high accuracy.
+    ///
+    /// Unlike [`spin_for`][BackOff::spin_for], the thread sleeps in the OS for
+    /// most of the duration and only spins for the final slack, which accounts
+    /// for the scheduler's wake-up jitter.
+    /// The slack is continuously calibrated based on the measured wake-up
+    /// jitter of previous calls and is shared across all threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use conquer_util::BackOff;
+    ///
+    /// let start = Instant::now();
+    /// BackOff::precise_sleep(Duration::from_micros(500));
+    /// assert!(start.elapsed() >= Duration::from_micros(500));
+    /// ```
+    #[inline]
+    pub fn precise_sleep(dur: Duration) {
+        Self::sleep_until(Instant::now() + dur);
+    }
+
+    /// Puts the current thread to sleep until the specified `deadline` with a
+    /// high accuracy.
+    ///
+    /// See [`precise_sleep`][BackOff::precise_sleep] for details.
+    #[inline]
+    pub fn sleep_until(deadline: Instant) {
+        sleep::sleep_until(deadline);
+    }
+
+    /// Returns the current slack, i.e. the final part of the duration, which
+    /// [`precise_sleep`][BackOff::precise_sleep] spins instead of sleeping.
+    #[inline]
+    pub fn sleep_slack() -> Duration {
+        sleep::slack()
+    }
+
+    /// Cooperatively yields the current thread.
+    ///
+    /// This is a convenience wrapper for
+    /// [`thread::yield_now`][std::thread::yield_now]
+    #[inline]
+    pub fn yield_now() {
+        std::thread::yield_now();
+    }
+
+    /// Calls `f` with a thread-local [`BackOff`] instance, which is reset
+    /// before each use.
+    ///
+    /// This avoids the cost of constructing a new instance in hot paths, which
+    /// is particularly relevant for randomized back-off: If the `random`
+    /// feature is enabled, the thread-local instance is created once through
+    /// [`random`][BackOff::random] and its generator is re-used afterwards.
+    /// Otherwise, a fixed exponential back-off strategy is used.
+    ///
+    /// If the thread-local instance is already in use (i.e. for nested calls)
+    /// or has already been destroyed, `f` is called with a fresh instance
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// use conquer_util::BackOff;
+    ///
+    /// let flag = AtomicBool::new(true);
+    /// BackOff::with_local(|backoff| {
+    ///     while !flag.load(Ordering::Acquire) {
+    ///         backoff.snooze();
+    ///     }
+    /// });
+    /// ```
+    #[inline]
+    pub fn with_local<R>(f: impl FnOnce(&mut BackOff) -> R) -> R {
+        std::thread_local!(static LOCAL: RefCell<BackOff> = RefCell::new(BackOff::local_init()));
+
+        let mut f = Some(f);
+        let res = LOCAL.try_with(|local| {
+            local.try_borrow_mut().ok().map(|mut backoff| {
+                backoff.reset();
+                (f.take().unwrap())(&mut backoff)
+            })
+        });
+
+        match res {
+            Ok(Some(res)) => res,
+            _ => (f.take().unwrap())(&mut BackOff::local_init()),
+        }
+    }
+
+    #[inline]
+    fn local_init() -> Self {
+        #[cfg(feature = "random")]
+        {
+            Self::random()
+        }
+
+        #[cfg(not(feature = "random"))]
+        {
+            Self::new()
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: BackOffStrategy> BackOff<S> {
+    /// Backs off by either spinning, yielding or putting the current thread to
+    /// sleep, depending on how often this method has already been called, and
+    /// returns which [`Snooze`] stage has been taken.
+    ///
+    /// As long as spinning is advisable (see
+    /// [`advise_yield`][BackOff::advise_yield]), this method behaves exactly
+    /// like [`spin`][BackOff::spin].
+    /// Afterwards, the current thread is yielded for the configured number of
+    /// rounds (see [`yield_limit`][BackOffConfig::yield_limit]).
+    /// If a sleep phase is configured (see [`sleep`][BackOffConfig::sleep]),
+    /// the current thread is then put to sleep for exponentially increasing
+    /// durations until the maximum duration is reached, from which point on
+    /// it sleeps for the maximum duration with each further call.
+    /// Otherwise, this method does nothing once all yielding rounds are used
+    /// up.
+    /// In either case, [`is_completed`][BackOff::is_completed] returns `true`
+    /// at this point and the caller should consider resorting to blocking
+    /// strategies such as parking the current thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// use conquer_util::BackOff;
+    ///
+    /// # let flag = AtomicBool::new(true);
+    /// let mut backoff = BackOff::new();
+    /// while !flag.load(Ordering::Acquire) {
+    ///     if backoff.is_completed() {
+    ///         std::thread::park();
+    ///     } else {
+    ///         backoff.snooze();
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    pub fn snooze(&mut self) -> Snooze {
+        if !self.strategy.advise_yield(self.attempt) {
+            self.spin();
+            Snooze::Spin
+        } else if self.yields < self.strategy.yield_limit() {
+            self.yields += 1;
+            BackOff::yield_now();
+            Snooze::Yield
+        } else if let Some((min, max)) = self.strategy.sleep_durations() {
+            std::thread::sleep(Self::sleep_duration(min, max, self.sleeps));
+            self.sleeps = self.sleeps.saturating_add(1);
+            Snooze::Sleep
+        } else {
+            Snooze::Exhausted
+        }
+    }
+
+    /// Returns `true` if spinning and yielding have been exhausted and further
+    /// calls to [`snooze`][BackOff::snooze] will either no longer back off or
+    /// only sleep for the configured maximum duration.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.strategy.advise_yield(self.attempt)
+            && self.yields >= self.strategy.yield_limit()
+            && match self.strategy.sleep_durations() {
+                Some((min, max)) => Self::sleep_duration(min, max, self.sleeps) >= max,
+                None => true,
+            }
+    }
+
+    #[inline]
+    fn sleep_duration(min: Duration, max: Duration, sleeps: u32) -> Duration {
+        // a zero `min` would never grow and hence never reach `max`
+        let min = min.max(Duration::from_nanos(1));
+        1u32.checked_shl(sleeps)
+            .and_then(|factor| min.checked_mul(factor))
+            .map_or(max, |dur| dur.min(max))
+    }
+
+    /// Backs off until the given condition `cond` is met or the `deadline`
+    /// has passed.
+    ///
+    /// See [`spin_until`][BackOff::spin_until] for details.
+    ///
+    /// # Errors
+    ///
+    /// Fails, if the condition is still not met once the `deadline` has
+    /// passed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use conquer_util::BackOff;
+    ///
+    /// let deadline = Instant::now() + Duration::from_micros(10);
+    /// let res = BackOff::new().spin_until_deadline(deadline, || false);
+    /// assert!(res.is_err());
+    /// ```
+    #[inline]
+    pub fn spin_until_deadline(
+        &mut self,
+        deadline: Instant,
+        cond: impl FnMut() -> bool,
+    ) -> Result<(), TimeoutError> {
+        self.spin_until_deadline_with_clock(&StdClock, deadline, cond)
+    }
+
+    /// Backs off as long as the given condition `cond` is met or until the
+    /// `deadline` has passed.
+    ///
+    /// See [`spin_until`][BackOff::spin_until] for details.
+    ///
+    /// # Errors
+    ///
+    /// Fails, if the condition is still met once the `deadline` has passed.
+    #[inline]
+    pub fn spin_while_deadline(
+        &mut self,
+        deadline: Instant,
+        mut cond: impl FnMut() -> bool,
+    ) -> Result<(), TimeoutError> {
+        self.spin_until_deadline(deadline, || !cond())
+    }
+}
+
+/********** impl Debug ****************************************************************************/
+
+impl<S: BackOffStrategy> fmt::Debug for BackOff<S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BackOff")
+            .field("advise_yield", &self.strategy.advise_yield(self.attempt))
+            .finish()
+    }
+}
+
+/********** impl Display **************************************************************************/
+
+impl<S: BackOffStrategy> fmt::Display for BackOff<S> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "advise yield: {}", self.strategy.advise_yield(self.attempt))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Snooze
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The stage taken by a single invocation of [`BackOff::snooze`].
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Snooze {
+    /// The current thread has spun for a bounded number of steps.
+    Spin,
+    /// The current thread has been yielded to the OS.
+    Yield,
+    /// The current thread has been put to sleep.
+    Sleep,
+    /// Both spinning and yielding have been exhausted, the current thread
+    /// should be blocked by other means.
+    Exhausted,
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BackOffExhausted
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An error for signalling that the budget of back-off rounds of a [`BackOff`]
+/// has been exhausted.
+#[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub struct BackOffExhausted(());
+
+/********** impl Display **************************************************************************/
+
+impl fmt::Display for BackOffExhausted {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "exhausted the maximum number of back-off attempts")
+    }
+}
+
+/********** impl Error ****************************************************************************/
+
+#[cfg(feature = "std")]
+impl std::error::Error for BackOffExhausted {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// TimeoutError
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An error for signalling that a condition has not been met before the
+/// maximum number of back-off attempts or the deadline has been exceeded.
+#[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub struct TimeoutError(());
+
+/********** impl Display **************************************************************************/
+
+impl fmt::Display for TimeoutError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "condition not met before exceeding the back-off budget or deadline")
+    }
+}
+
+/********** impl Error ****************************************************************************/
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimeoutError {}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BackOffConfig
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A `const` constructible configuration for tuning the parameters of a
+/// [`BackOff`] instance.
+///
+/// The default values have been chosen based on measurements on an Intel(R)
+/// i5 CPU and may not be optimal for other platforms.
+///
+/// # Examples
+///
+/// ```
+/// use conquer_util::BackOffConfig;
+///
+/// const SMALL: BackOffConfig = BackOffConfig::new().spin_limit_pow(5).max_steps(16);
+/// const LARGE: BackOffConfig = BackOffConfig::new().init_pow(2).spin_limit_pow(10);
+/// ```
+#[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub struct BackOffConfig {
+    init_pow: u32,
+    spin_limit_pow: u32,
+    step_multiplier: u32,
+    max_steps: u32,
+    yield_limit: u32,
+    max_attempts: Option<u32>,
+    #[cfg(feature = "std")]
+    sleep: Option<(Duration, Duration)>,
+    growth: Growth,
+    #[cfg(feature = "random")]
+    jitter: Jitter,
+}
+
+/********** impl Default **************************************************************************/
+
+impl Default for BackOffConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/********** impl inherent *************************************************************************/
+
+impl BackOffConfig {
+    /// The default initial exponent.
+    pub const DEFAULT_INIT_POW: u32 = 1;
+    /// The default exponent at which spinning is no longer advised.
+    pub const DEFAULT_SPIN_LIMIT_POW: u32 = 7;
+    /// The default number of yielding rounds after spinning has been
+    /// exhausted.
+    pub const DEFAULT_YIELD_LIMIT: u32 = 10;
+    /// The number of CPUs from which on [`scale_to_cpus`][BackOffConfig::scale_to_cpus]
+    /// leaves the spin limit unchanged.
+    pub const SCALE_REFERENCE_CPUS: usize = 16;
+
+    /// Creates a new [`BackOffConfig`] with the default parameters.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            init_pow: Self::DEFAULT_INIT_POW,
+            spin_limit_pow: Self::DEFAULT_SPIN_LIMIT_POW,
+            step_multiplier: 1,
+            max_steps: u32::MAX,
+            yield_limit: Self::DEFAULT_YIELD_LIMIT,
+            max_attempts: None,
+            #[cfg(feature = "std")]
+            sleep: None,
+            growth: Growth::Exponential,
+            #[cfg(feature = "random")]
+            jitter: Jitter::Half,
+        }
+    }
+
+    /// Creates a new [`BackOffConfig`], which chooses the initial and limit
+    /// exponents such that the first back-off round spins for approximately
+    /// `min` and yielding is advised once a round takes approximately `max`.
+    ///
+    /// The durations are translated into spin steps based on the measured
+    /// cost of a single spin step on the current CPU (see
+    /// [`spin_once_cost`][BackOff::spin_once_cost]), so the wall-clock
+    /// behaviour is comparable across different CPUs.
+    /// Since exponents are used internally, both durations are rounded up to
+    /// the next power of two spin steps.
+    /// The exponents are chosen for [`Growth::Exponential`], so the durations
+    /// no longer apply if a different [`growth`][BackOffConfig::growth] curve
+    /// is set afterwards.
+    ///
+    /// # Panics
+    ///
+    /// This function panics, if `min` is greater than `max`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use conquer_util::{BackOff, BackOffConfig};
+    ///
+    /// let config =
+    ///     BackOffConfig::from_durations(Duration::from_nanos(50), Duration::from_micros(1));
+    /// let mut backoff = BackOff::with_config(config);
+    /// while !backoff.advise_yield() {
+    ///     backoff.spin();
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn from_durations(min: Duration, max: Duration) -> Self {
+        assert!(min <= max, "`min` must not be greater than `max`");
+        Self::new().init_pow(calibrate::pow_for(min)).spin_limit_pow(calibrate::pow_for(max))
+    }
+
+    /// Scales the spin limit to the number of CPUs usable by the current
+    /// process as reported by [`available_parallelism`][std::thread::available_parallelism],
+    /// which e.g. respects the CPU affinity mask on Linux.
+    ///
+    /// The number of CPUs is queried anew on every call, so the result should
+    /// be stored rather than re-computed in hot loops.
+    /// If the number of CPUs can not be determined, the configuration is
+    /// returned unchanged.
+    /// See [`scale_to_cpus`][BackOffConfig::scale_to_cpus] for details.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn scale_to_available_parallelism(self) -> Self {
+        match std::thread::available_parallelism() {
+            Ok(cpus) => self.scale_to_cpus(cpus.get()),
+            Err(_) => self,
+        }
+    }
+
+    /// Scales the spin limit to the given number of usable `cpus`.
+    ///
+    /// Spinning only makes sense while the thread holding a contended resource
+    /// can make progress on another CPU.
+    /// Hence, for a single CPU spinning is disabled altogether and yielding is
+    /// advised right away.
+    /// Otherwise, the spin limit exponent is reduced by one for each halving
+    /// of the number of CPUs below [`SCALE_REFERENCE_CPUS`][BackOffConfig::SCALE_REFERENCE_CPUS],
+    /// but never below the initial exponent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use conquer_util::{BackOff, BackOffConfig};
+    ///
+    /// let mut backoff = BackOff::with_config(BackOffConfig::new().scale_to_cpus(1));
+    /// assert!(backoff.advise_yield());
+    /// ```
+    #[inline]
+    pub const fn scale_to_cpus(self, cpus: usize) -> Self {
+        const REFERENCE_LOG2: u32 = BackOffConfig::SCALE_REFERENCE_CPUS.trailing_zeros();
+
+        if cpus <= 1 {
+            return Self { spin_limit_pow: self.init_pow, max_steps: 0, ..self };
+        }
+
+        let log2 = (usize::BITS - 1) - cpus.leading_zeros();
+        let deficit = REFERENCE_LOG2.saturating_sub(log2);
+        let spin_limit_pow = self.spin_limit_pow.saturating_sub(deficit);
+        let spin_limit_pow =
+            if spin_limit_pow > self.init_pow { spin_limit_pow } else { self.init_pow };
+        Self { spin_limit_pow, ..self }
+    }
+
+    /// Sets the initial exponent, i.e. a fresh or reset [`BackOff`] will spin
+    /// for `2^init_pow` steps on the first invocation of
+    /// [`spin`][BackOff::spin].
+    #[inline]
+    pub const fn init_pow(self, init_pow: u32) -> Self {
+        Self { init_pow, ..self }
+    }
+
+    /// Sets the exponent at which the exponential growth of spin steps stops
+    /// and [`advise_yield`][BackOff::advise_yield] starts to return `true`.
+    ///
+    /// If the limit is lower than the initial exponent, yielding is advised
+    /// right away.
+    #[inline]
+    pub const fn spin_limit_pow(self, spin_limit_pow: u32) -> Self {
+        Self { spin_limit_pow, ..self }
+    }
+
+    /// Sets the factor by which the number of steps of each back-off round is
+    /// multiplied.
+    #[inline]
+    pub const fn step_multiplier(self, step_multiplier: u32) -> Self {
+        Self { step_multiplier, ..self }
+    }
+
+    /// Sets the maximum number of steps for any single back-off round,
+    /// regardless of the current exponent and step multiplier.
+    #[inline]
+    pub const fn max_steps(self, max_steps: u32) -> Self {
+        Self { max_steps, ..self }
+    }
+
+    /// Sets the number of times [`snooze`][BackOff::snooze] yields the current
+    /// thread once spinning is no longer advised, before the back-off is
+    /// considered to be completed.
+    #[inline]
+    pub const fn yield_limit(self, yield_limit: u32) -> Self {
+        Self { yield_limit, ..self }
+    }
+
+    /// Sets the maximum number of back-off rounds, after which
+    /// [`try_spin`][BackOff::try_spin] fails until the [`BackOff`] is reset.
+    ///
+    /// By default, the number of back-off rounds is unlimited.
+    #[inline]
+    pub const fn max_attempts(self, max_attempts: u32) -> Self {
+        Self { max_attempts: Some(max_attempts), ..self }
+    }
+
+    /// Enables the sleep phase of [`snooze`][BackOff::snooze], which follows
+    /// once all yielding rounds are used up.
+    ///
+    /// The first sleep lasts for `min` and each subsequent sleep doubles the
+    /// duration, until it is capped at `max`.
+    /// A zero `min` is treated as one nanosecond, so the sleep phase always
+    /// reaches `max` eventually.
+    /// Resetting the [`BackOff`] returns it to spinning.
+    ///
+    /// # Panics
+    ///
+    /// This function panics in debug builds, if `min` is greater than `max`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use conquer_util::{BackOff, BackOffConfig, Snooze};
+    ///
+    /// const CONFIG: BackOffConfig = BackOffConfig::new()
+    ///     .yield_limit(0)
+    ///     .sleep(Duration::from_micros(1), Duration::from_micros(8));
+    ///
+    /// let mut backoff = BackOff::with_config(CONFIG);
+    /// while !backoff.advise_yield() {
+    ///     backoff.spin();
+    /// }
+    ///
+    /// assert_eq!(backoff.snooze(), Snooze::Sleep);
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub const fn sleep(self, min: Duration, max: Duration) -> Self {
+        debug_assert!(min.as_nanos() <= max.as_nanos(), "`min` must not be greater than `max`");
+        Self { sleep: Some((min, max)), ..self }
+    }
+
+    /// Sets the [`Growth`] curve, by which the number of spin steps increases
+    /// with each back-off round.
+    ///
+    /// For curves other than [`Exponential`][Growth::Exponential], the
+    /// initial and limit exponents denote the first and last point of the
+    /// respective curve.
+    #[inline]
+    pub const fn growth(self, growth: Growth) -> Self {
+        Self { growth, ..self }
+    }
+
+    /// Sets the [`Jitter`] mode for randomized back-off strategies.
+    ///
+    /// This setting has no effect on non-randomized strategies.
+    #[cfg(feature = "random")]
+    #[inline]
+    pub const fn jitter(self, jitter: Jitter) -> Self {
+        Self { jitter, ..self }
+    }
+
+    #[inline]
+    fn pow(&self, attempt: u32) -> u32 {
+        self.init_pow.max(self.init_pow.saturating_add(attempt).min(self.spin_limit_pow))
+    }
+
+    #[inline]
+    fn steps(&self, pow: u32) -> u32 {
+        self.growth.steps(pow).saturating_mul(self.step_multiplier).min(self.max_steps)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Growth
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The curve by which the number of spin steps grows with each back-off
+/// round.
+///
+/// In the following, `n` denotes the current exponent, which starts out at the
+/// configured [`init_pow`][BackOffConfig::init_pow] and increases by one with
+/// each round until the [`spin_limit_pow`][BackOffConfig::spin_limit_pow] is
+/// reached.
+#[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Growth {
+    /// Spins for `2^n` steps (default).
+    Exponential,
+    /// Spins for `n` steps.
+    Linear,
+    /// Spins for `fib(n + 1)` steps, i.e. `1, 2, 3, 5, 8, ...`.
+    Fibonacci,
+    /// Spins for `n^k` steps with `k` being the given degree.
+    Polynomial(u32),
+}
+
+/********** impl inherent *************************************************************************/
+
+impl Growth {
+    #[inline]
+    fn steps(self, n: u32) -> u32 {
+        match self {
+            Growth::Exponential => 1u32.checked_shl(n).unwrap_or(u32::MAX),
+            Growth::Linear => n,
+            Growth::Fibonacci => {
+                // fib(48) is the first Fibonacci number exceeding `u32::MAX`
+                let (mut curr, mut next) = (1u32, 2u32);
+                for _ in 0..n.min(48) {
+                    let sum = curr.saturating_add(next);
+                    curr = next;
+                    next = sum;
+                }
+
+                curr
+            }
+            Growth::Polynomial(degree) => n.saturating_pow(degree),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    #[cfg(feature = "std")]
+    use super::DefaultStrategy;
+    use super::{BackOff, BackOffConfig, BackOffStrategy, Growth};
+
+    #[test]
+    fn spin_full_const() {
+        let mut backoff = BackOff::new();
+        let mut steps = 1;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, BackOffConfig::DEFAULT_SPIN_LIMIT_POW);
+    }
+
+    #[test]
+    fn spin_full_linear() {
+        let mut backoff = BackOff::with_config(BackOffConfig::new().growth(Growth::Linear));
+        let mut steps = 1;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, BackOffConfig::DEFAULT_SPIN_LIMIT_POW);
+        assert_eq!(
+            (1..8).map(|n| Growth::Linear.steps(n)).collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn spin_full_fibonacci() {
+        let mut backoff = BackOff::with_config(BackOffConfig::new().growth(Growth::Fibonacci));
+        let mut steps = 1;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, BackOffConfig::DEFAULT_SPIN_LIMIT_POW);
+        assert_eq!(
+            (0..7).map(|n| Growth::Fibonacci.steps(n)).collect::<Vec<_>>(),
+            [1, 2, 3, 5, 8, 13, 21]
+        );
+        assert_eq!(Growth::Fibonacci.steps(100), u32::MAX);
+    }
+
+    #[test]
+    fn spin_full_polynomial() {
+        let config = BackOffConfig::new().growth(Growth::Polynomial(3)).max_steps(100);
+        let mut backoff = BackOff::with_config(config);
+        let mut steps = 1;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, BackOffConfig::DEFAULT_SPIN_LIMIT_POW);
+        assert_eq!((1..6).map(|n| config.steps(n)).collect::<Vec<_>>(), [1, 8, 27, 64, 100]);
+    }
+
+    #[test]
+    fn spin_full_config() {
+        const CONFIG: BackOffConfig = BackOffConfig::new().init_pow(3).spin_limit_pow(10);
+
+        let mut backoff = BackOff::with_config(CONFIG);
+        let mut steps = 3;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, 10);
+        backoff.reset();
+        assert!(!backoff.advise_yield());
+    }
+
+    #[test]
+    fn config_steps() {
+        let config = BackOffConfig::new().step_multiplier(3).max_steps(100);
+        assert_eq!(config.steps(1), 6);
+        assert_eq!(config.steps(5), 96);
+        assert_eq!(config.steps(6), 100);
+        assert_eq!(config.steps(40), 100);
+
+        let config = BackOffConfig::new().spin_limit_pow(0);
+        assert!(BackOff::with_config(config).advise_yield());
+    }
+
+    #[test]
+    fn spin_full_custom() {
+        struct Fixed(u32);
+
+        impl BackOffStrategy for Fixed {
+            fn spin_steps(&mut self, attempt: u32) -> u32 {
+                assert!(attempt < self.0);
+                1
+            }
+
+            fn advise_yield(&self, attempt: u32) -> bool {
+                attempt == self.0
+            }
+        }
+
+        let mut backoff = BackOff::with_strategy(Fixed(4));
+        let mut steps = 0;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, 4);
+        backoff.reset();
+        assert!(!backoff.advise_yield());
+    }
+
+    #[test]
+    fn adaptive() {
+        let mut backoff = BackOff::adaptive();
+        for _ in 0..4 {
+            backoff.failure();
+        }
+
+        assert_eq!(backoff.strategy().offset(), 4);
+        backoff.reset();
+        assert_eq!(backoff.strategy().offset(), 4);
+
+        let mut steps = 1 + 4;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, BackOffConfig::DEFAULT_SPIN_LIMIT_POW);
+
+        for _ in 0..10 {
+            backoff.failure();
+        }
+
+        backoff.reset();
+        assert!(!backoff.advise_yield());
+        assert_eq!(backoff.strategy().offset(), 5);
+        backoff.success();
+        assert_eq!(backoff.strategy().offset(), 2);
+        backoff.success();
+        backoff.success();
+        assert_eq!(backoff.strategy().offset(), 0);
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn spin_full_random() {
+        let mut backoff = BackOff::random();
+        let mut steps = 1;
+        while !backoff.advise_yield() {
+            backoff.spin();
+            steps += 1;
+        }
+
+        assert_eq!(steps, BackOffConfig::DEFAULT_SPIN_LIMIT_POW);
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn spin_full_jitter() {
+        use super::Jitter;
+
+        for &jitter in &[Jitter::Half, Jitter::Full, Jitter::Equal, Jitter::Decorrelated] {
+            let mut backoff = BackOff::random_with_config(BackOffConfig::new().jitter(jitter));
+            let mut steps = 1;
+            while !backoff.advise_yield() {
+                backoff.spin();
+                steps += 1;
+            }
+
+            assert_eq!(steps, BackOffConfig::DEFAULT_SPIN_LIMIT_POW);
+        }
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn jitter_bounds() {
+        use super::{DefaultStrategy, Jitter};
+
+        let config = BackOffConfig::new().jitter(Jitter::Full);
+        let mut strategy = DefaultStrategy::random_with_seed(0, config);
+        assert!((0..100).all(|attempt| strategy.spin_steps(attempt) <= 128));
+
+        let config = BackOffConfig::new().jitter(Jitter::Equal);
+        let mut strategy = DefaultStrategy::random_with_seed(0, config);
+        assert!((0..100).all(|_| (2..=4).contains(&strategy.spin_steps(1))));
+
+        let config = BackOffConfig::new().jitter(Jitter::Decorrelated);
+        let mut strategy = DefaultStrategy::random_with_seed(0, config);
+        assert!((0..100).all(|attempt| (2..=128).contains(&strategy.spin_steps(attempt))));
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn jitter_decorrelated_zero_base() {
+        use super::{DefaultStrategy, Jitter};
+
+        let config =
+            BackOffConfig::new().init_pow(0).growth(Growth::Linear).jitter(Jitter::Decorrelated);
+        let mut strategy = DefaultStrategy::random_with_seed(0, config);
+        let steps: Vec<_> = (0..100).map(|attempt| strategy.spin_steps(attempt)).collect();
+        assert!(steps.iter().all(|&steps| (1..=7).contains(&steps)));
+        assert!(steps.iter().any(|&steps| steps > 1));
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn random_with_rng() {
+        use super::{BackOffRng, DefaultStrategy, Jitter, RandomStrategy, WyRand};
+
+        /// An RNG cycling through a fixed sequence of values.
+        struct Sequence(&'static [u64], usize);
+
+        impl BackOffRng for Sequence {
+            fn next_u64(&mut self) -> u64 {
+                let value = self.0[self.1 % self.0.len()];
+                self.1 += 1;
+                value
+            }
+        }
+
+        // `0` always selects the lower bound, `u64::MAX` the upper bound
+        let config = BackOffConfig::new().jitter(Jitter::Equal);
+        let mut strategy = RandomStrategy::new(Sequence(&[0, u64::MAX], 0), config);
+        assert_eq!(strategy.spin_steps(2), 4);
+        assert_eq!(strategy.spin_steps(2), 8);
+
+        let mut backoff = BackOff::random_with_rng(Sequence(&[0], 0));
+        while !backoff.advise_yield() {
+            backoff.spin();
+        }
+
+        // the built-in generator yields the same sequence as the default strategy
+        let mut a = RandomStrategy::new(WyRand::new(7), BackOffConfig::new());
+        let mut b = DefaultStrategy::random_with_seed(7, BackOffConfig::new());
+        assert!((0..100).all(|attempt| a.spin_steps(attempt) == b.spin_steps(attempt)));
+    }
+
+    // this is the only test writing the global seed, since tests run in
+    // parallel
+    #[cfg(feature = "random")]
+    #[test]
+    fn global_seed() {
+        use super::DefaultStrategy;
+
+        const SEED: u64 = 0x1234_5678;
+
+        DefaultStrategy::set_global_seed(SEED);
+        let mut random = DefaultStrategy::random(BackOffConfig::new());
+        let steps: Vec<_> = (0..32).map(|attempt| random.spin_steps(attempt)).collect();
+
+        // other tests may concurrently advance the global seed sequence
+        let reproduced = (0..16).any(|i| {
+            let seed = SEED + i * DefaultStrategy::SEED_INCREMENT;
+            let mut seeded = DefaultStrategy::random_with_seed(seed, BackOffConfig::new());
+            (0..32).all(|attempt| seeded.spin_steps(attempt) == steps[attempt as usize])
+        });
+        assert!(reproduced);
+
+        #[cfg(feature = "std")]
+        {
+            let a = DefaultStrategy::seed_global_from_entropy();
+            let b = DefaultStrategy::seed_global_from_entropy();
+            assert_ne!(a, b);
+        }
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn random_with_seed_deterministic() {
+        use super::DefaultStrategy;
+
+        let mut a = DefaultStrategy::random_with_seed(7, BackOffConfig::new());
+        let mut b = DefaultStrategy::random_with_seed(7, BackOffConfig::new());
+        assert!((0..100).all(|attempt| a.spin_steps(attempt) == b.spin_steps(attempt)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn with_durations() {
+        use std::time::Duration;
+
+        let mut backoff =
+            BackOff::with_durations(Duration::from_nanos(10), Duration::from_micros(1));
+        while !backoff.advise_yield() {
+            backoff.spin();
+        }
+
+        assert!(BackOff::spin_once_cost() < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn try_spin() {
+        let mut backoff = BackOff::with_config(BackOffConfig::new().max_attempts(10));
+        let mut rounds = 0;
+        while backoff.try_spin().is_ok() {
+            rounds += 1;
+        }
+
+        assert_eq!(rounds, 10);
+        assert!(backoff.is_exhausted());
+        backoff.success();
+        assert!(!backoff.is_exhausted());
+
+        let mut backoff = BackOff::new();
+        assert!((0..100).all(|_| backoff.try_spin().is_ok()));
+    }
+
+    #[test]
+    fn spin_until() {
+        let mut backoff = BackOff::new();
+        let mut count = 0;
+        backoff.spin_until(|| {
+            count += 1;
+            count == 10
+        });
+        assert_eq!(count, 10);
+
+        backoff.reset();
+        backoff.spin_while(|| {
+            count -= 1;
+            count > 0
+        });
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn spin_until_bounded() {
+        let mut backoff = BackOff::new();
+        let mut count = 0;
+        assert!(backoff
+            .spin_until_bounded(4, || {
+                count += 1;
+                false
+            })
+            .is_err());
+        assert_eq!(count, 5);
+
+        backoff.reset();
+        assert!(backoff.spin_until_bounded(0, || true).is_ok());
+        assert!(backoff
+            .spin_while_bounded(4, || {
+                count += 1;
+                count < 7
+            })
+            .is_ok());
+        assert_eq!(count, 7);
+    }
+
+    #[test]
+    fn spin_with_clock() {
+        use core::cell::Cell;
+        use core::time::Duration;
+
+        use super::Clock;
+
+        struct Ticks(Cell<u64>);
+
+        impl Clock for Ticks {
+            type Instant = u64;
+
+            fn now(&self) -> u64 {
+                let now = self.0.get();
+                self.0.set(now + 1);
+                now
+            }
+
+            fn add(&self, instant: u64, dur: Duration) -> u64 {
+                instant + dur.as_nanos() as u64
+            }
+        }
+
+        let ticks = Ticks(Cell::new(0));
+        BackOff::spin_for_with_clock(&ticks, Duration::from_nanos(100));
+        assert_eq!(ticks.0.get(), 101);
+
+        let mut backoff = BackOff::new();
+        let deadline = ticks.add(ticks.now(), Duration::from_nanos(10));
+        assert!(backoff.spin_while_deadline_with_clock(&ticks, deadline, || true).is_err());
+        assert!(ticks.0.get() > deadline);
+
+        backoff.reset();
+        let deadline = ticks.add(ticks.now(), Duration::from_nanos(10));
+        assert!(backoff.spin_until_deadline_with_clock(&ticks, deadline, || true).is_ok());
+    }
+
+    #[test]
+    fn scale_to_cpus() {
+        let config = BackOffConfig::new().scale_to_cpus(1);
+        let mut backoff = BackOff::with_config(config);
+        assert!(backoff.advise_yield());
+        assert_eq!(backoff.strategy.spin_steps(0), 0);
+
+        let limits: Vec<_> =
+            [2, 3, 4, 8, 16, 64].iter().map(|&n| BackOffConfig::new().scale_to_cpus(n)).collect();
+        let limits: Vec<_> = limits.iter().map(|config| config.spin_limit_pow).collect();
+        assert_eq!(limits, [4, 4, 5, 6, 7, 7]);
+
+        let config = BackOffConfig::new().init_pow(5).scale_to_cpus(2);
+        assert_eq!(config.spin_limit_pow, 5);
+    }
+
+    #[cfg(all(feature = "std", target_os = "linux"))]
+    #[test]
+    fn cpu_aware_single_cpu_affinity() {
+        use std::thread;
+
+        use super::Snooze;
+
+        extern "C" {
+            fn sched_getaffinity(pid: i32, size: usize, mask: *mut u64) -> i32;
+            fn sched_setaffinity(pid: i32, size: usize, mask: *const u64) -> i32;
+        }
+
+        // restricting the affinity of a dedicated thread leaves the other
+        // tests unaffected
+        thread::spawn(|| {
+            let mut mask = [0u64; 16];
+            let size = core::mem::size_of_val(&mask);
+            assert_eq!(unsafe { sched_getaffinity(0, size, mask.as_mut_ptr()) }, 0);
+
+            let (word, bits) = mask.iter().enumerate().find(|&(_, &bits)| bits != 0).unwrap();
+            let mut single = [0u64; 16];
+            single[word] = 1 << bits.trailing_zeros();
+            assert_eq!(unsafe { sched_setaffinity(0, size, single.as_ptr()) }, 0);
+
+            assert_eq!(thread::available_parallelism().unwrap().get(), 1);
+            let mut backoff = BackOff::cpu_aware();
+            assert!(backoff.advise_yield());
+            assert_eq!(backoff.snooze(), Snooze::Yield);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn with_local() {
+        BackOff::with_local(|backoff| {
+            while !backoff.advise_yield() {
+                backoff.spin();
+            }
+        });
+
+        // the thread-local instance is reset before being used again
+        BackOff::with_local(|backoff| assert!(!backoff.advise_yield()));
+
+        // nested calls fall back to a fresh instance
+        BackOff::with_local(|outer| {
+            while !outer.advise_yield() {
+                outer.spin();
+            }
+
+            BackOff::with_local(|inner| assert!(!inner.advise_yield()));
+            assert!(outer.advise_yield());
+        });
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn spin_until_deadline() {
+        use std::time::{Duration, Instant};
+
+        let mut backoff = BackOff::new();
+        let deadline = Instant::now() + Duration::from_millis(1);
+        assert!(backoff.spin_while_deadline(deadline, || true).is_err());
+        assert!(Instant::now() >= deadline);
+
+        backoff.reset();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut count = 0;
+        assert!(backoff
+            .spin_until_deadline(deadline, || {
+                count += 1;
+                count == 100
+            })
+            .is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn snooze_sleep() {
+        use std::time::{Duration, Instant};
+
+        use super::Snooze;
+
+        let (min, max) = (Duration::from_micros(100), Duration::from_micros(400));
+        let config = BackOffConfig::new().spin_limit_pow(2).yield_limit(1).sleep(min, max);
+        let mut backoff = BackOff::with_config(config);
+        assert_eq!(backoff.snooze(), Snooze::Spin);
+        assert_eq!(backoff.snooze(), Snooze::Yield);
+
+        let start = Instant::now();
+        for _ in 0..2 {
+            assert!(!backoff.is_completed());
+            assert_eq!(backoff.snooze(), Snooze::Sleep);
+        }
+
+        assert!(backoff.is_completed());
+        assert_eq!(backoff.snooze(), Snooze::Sleep);
+        assert!(start.elapsed() >= Duration::from_micros(100 + 200 + 400));
+
+        backoff.reset();
+        assert_eq!(backoff.snooze(), Snooze::Spin);
+        assert_eq!(BackOff::<DefaultStrategy>::sleep_duration(min, max, 40), max);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn snooze_sleep_zero_min() {
+        use std::time::Duration;
+
+        let config = BackOffConfig::new()
+            .spin_limit_pow(0)
+            .yield_limit(0)
+            .sleep(Duration::from_nanos(0), Duration::from_micros(1));
+        let mut backoff = BackOff::with_config(config);
+        let mut sleeps = 0;
+        while !backoff.is_completed() && sleeps < 16 {
+            backoff.snooze();
+            sleeps += 1;
+        }
+
+        assert_eq!(sleeps, 10);
+        assert!(backoff.is_completed());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn snooze_stages() {
+        use super::Snooze;
+
+        let mut backoff =
+            BackOff::with_config(BackOffConfig::new().spin_limit_pow(3).yield_limit(2));
+        let stages: Vec<_> = (0..6).map(|_| backoff.snooze()).collect();
+        assert_eq!(
+            stages,
+            [
+                Snooze::Spin,
+                Snooze::Spin,
+                Snooze::Yield,
+                Snooze::Yield,
+                Snooze::Exhausted,
+                Snooze::Exhausted
+            ]
+        );
+        assert!(backoff.is_completed());
+
+        backoff.reset();
+        assert!(!backoff.is_completed());
+        assert_eq!(backoff.snooze(), Snooze::Spin);
+    }
+}
@@ -0,0 +1,237 @@
+//! A tiny, dependency-free pseudo random number generator for randomized
+//! back-off strategies.
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BackOffRng
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A source of random numbers for randomized back-off strategies (see
+/// [`RandomStrategy`][crate::RandomStrategy]).
+///
+/// The built-in [`WyRand`] generator implements this trait.
+/// If the `rand` feature is enabled, all generators of the `rand` crate, e.g.
+/// hardware RNGs or existing per-thread generators, can be used through the
+/// [`RandRng`] adapter.
+pub trait BackOffRng {
+    /// Returns the next random `u64` value.
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a random value in the range `[low, high)`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics, if `low` is not less than `high`.
+    #[inline]
+    fn gen_range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "`low` must be less than `high`");
+        // maps the value into the range through a widening multiplication,
+        // the resulting bias is negligible for the small ranges required for
+        // back-off
+        let range = u128::from(high - low);
+        low + ((u128::from(self.next_u64()) * range) >> 64) as u64
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// RandRng
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An adapter implementing [`BackOffRng`] for any generator implementing the
+/// `RngCore` trait of the `rand` crate.
+///
+/// # Examples
+///
+/// ```
+/// use conquer_util::{BackOff, RandRng};
+/// use rand::rngs::mock::StepRng;
+///
+/// let mut backoff = BackOff::random_with_rng(RandRng(StepRng::new(0, 1 << 60)));
+/// while !backoff.advise_yield() {
+///     backoff.spin();
+/// }
+/// ```
+#[cfg(feature = "rand")]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub struct RandRng<R>(pub R);
+
+/********** impl BackOffRng ***********************************************************************/
+
+#[cfg(feature = "rand")]
+impl<R: rand::RngCore> BackOffRng for RandRng<R> {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// WyRand
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A small and fast pseudo random number generator based on the *wyrand*
+/// algorithm, which is used by randomized back-off strategies.
+///
+/// The generator is **not** cryptographically secure, but its output is fully
+/// determined by its seed and it can be created in `const` contexts.
+///
+/// If the `rand` feature is enabled, this type also implements the
+/// `RngCore` and `SeedableRng` traits of the `rand` crate.
+///
+/// # Examples
+///
+/// ```
+/// use conquer_util::WyRand;
+///
+/// static SEEDED: WyRand = WyRand::new(0xdead_beef);
+///
+/// let (mut a, mut b) = (SEEDED.clone(), SEEDED.clone());
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// assert!(a.gen_range(10, 20) >= 10);
+/// ```
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct WyRand {
+    state: u64,
+}
+
+/********** impl inherent *************************************************************************/
+
+impl WyRand {
+    const INCREMENT: u64 = 0xa076_1d64_78bd_642f;
+    const XOR: u64 = 0xe703_7ed1_a0b4_28db;
+
+    /// Creates a new generator from the given `seed`.
+    #[inline]
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next pseudo random `u64` value.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(Self::INCREMENT);
+        let t = u128::from(self.state) * u128::from(self.state ^ Self::XOR);
+        ((t >> 64) as u64) ^ (t as u64)
+    }
+
+    /// Returns a pseudo random value in the range `[low, high)`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics, if `low` is not less than `high`.
+    #[inline]
+    pub fn gen_range(&mut self, low: u64, high: u64) -> u64 {
+        BackOffRng::gen_range(self, low, high)
+    }
+}
+
+/********** impl BackOffRng ***********************************************************************/
+
+impl BackOffRng for WyRand {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        WyRand::next_u64(self)
+    }
+}
+
+/********** impl RngCore **************************************************************************/
+
+#[cfg(feature = "rand")]
+impl rand::RngCore for WyRand {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        WyRand::next_u64(self)
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = WyRand::next_u64(self).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/********** impl SeedableRng **********************************************************************/
+
+#[cfg(feature = "rand")]
+impl rand::SeedableRng for WyRand {
+    type Seed = [u8; 8];
+
+    #[inline]
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(u64::from_le_bytes(seed))
+    }
+
+    #[inline]
+    fn seed_from_u64(state: u64) -> Self {
+        Self::new(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WyRand;
+
+    #[test]
+    fn deterministic() {
+        const SEEDED: WyRand = WyRand::new(42);
+
+        let (mut a, mut b) = (SEEDED, SEEDED);
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+
+        let mut c = WyRand::new(43);
+        assert_ne!(a.next_u64(), c.next_u64());
+    }
+
+    #[test]
+    fn gen_range() {
+        let mut rng = WyRand::new(0);
+        assert!((0..1000).all(|_| (2..4).contains(&rng.gen_range(2, 4))));
+        assert!((0..1000).all(|_| rng.gen_range(7, 8) == 7));
+
+        let mut seen = [false; 16];
+        for _ in 0..1000 {
+            seen[rng.gen_range(0, 16) as usize] = true;
+        }
+
+        assert!(seen.iter().all(|&seen| seen));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn rng_core() {
+        use rand::{Rng, RngCore, SeedableRng};
+
+        let mut rng = WyRand::seed_from_u64(42);
+        let mut expected = WyRand::new(42);
+        assert_eq!(RngCore::next_u64(&mut rng), expected.next_u64());
+
+        let mut bytes = [0u8; 12];
+        rng.fill_bytes(&mut bytes);
+        assert_eq!(bytes[..8], expected.next_u64().to_le_bytes());
+        assert!(Rng::gen_range(&mut rng, 0, 10) < 10);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn rand_rng() {
+        use rand::rngs::mock::StepRng;
+
+        use super::{BackOffRng, RandRng};
+
+        let mut rng = RandRng(StepRng::new(0, u64::MAX / 4 + 1));
+        let values: [u64; 4] = [0, 0, 0, 0].map(|_| rng.gen_range(0, 4));
+        assert_eq!(values, [0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,276 @@
+//! A shareable back-off type, which tracks the contention on a shared memory
+//! location across all participating threads.
+
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::align::CacheAligned;
+
+use super::{BackOff, BackOffConfig, BackOffStrategy, DefaultStrategy};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// AtomicBackOff
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A thread-safe, cache-aligned contention estimate that can be embedded in
+/// concurrent data structures and shared between threads.
+///
+/// Each thread creates its own [`BackOff`] handle through
+/// [`backoff`][AtomicBackOff::backoff], which reports the success or failure
+/// of its operations (see [`BackOff::success`] and [`BackOff::failure`]) to
+/// the shared estimate.
+/// Each failure advances the starting point of all handles' back-off by one
+/// round, while each success halves the current advance, so that a contended
+/// memory location results in longer initial spins for every participating
+/// thread.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use conquer_util::AtomicBackOff;
+///
+/// struct Counter {
+///     count: AtomicUsize,
+///     backoff: AtomicBackOff,
+/// }
+///
+/// impl Counter {
+///     fn increment(&self) {
+///         let mut backoff = self.backoff.backoff();
+///         let mut curr = self.count.load(Ordering::Relaxed);
+///         let mut contended = false;
+///         while let Err(actual) = self.count.compare_exchange_weak(
+///             curr,
+///             curr + 1,
+///             Ordering::Relaxed,
+///             Ordering::Relaxed,
+///         ) {
+///             curr = actual;
+///             contended = true;
+///             backoff.spin();
+///         }
+///
+///         if contended {
+///             backoff.failure();
+///         } else {
+///             backoff.success();
+///         }
+///     }
+/// }
+///
+/// let counter = Counter { count: AtomicUsize::new(0), backoff: AtomicBackOff::new() };
+/// counter.increment();
+/// assert_eq!(counter.count.load(Ordering::Relaxed), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct AtomicBackOff {
+    contention: CacheAligned<AtomicU32>,
+    config: BackOffConfig,
+}
+
+/********** impl inherent *************************************************************************/
+
+impl AtomicBackOff {
+    /// Creates a new [`AtomicBackOff`] with the default configuration.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_config(BackOffConfig::new())
+    }
+
+    /// Creates a new [`AtomicBackOff`] using the given `config` for all of its
+    /// handles.
+    #[inline]
+    pub const fn with_config(config: BackOffConfig) -> Self {
+        Self { contention: CacheAligned::new(AtomicU32::new(0)), config }
+    }
+
+    /// Returns the current contention estimate, i.e. the number of back-off
+    /// rounds by which the starting point of all handles is advanced.
+    #[inline]
+    pub fn contention(&self) -> u32 {
+        self.contention.get().load(Ordering::Relaxed)
+    }
+
+    /// Returns a new thread local [`BackOff`] handle with a fixed exponential
+    /// back-off strategy, which consults and updates the shared contention
+    /// estimate.
+    #[inline]
+    pub fn backoff(&self) -> BackOff<AtomicStrategy<'_>> {
+        BackOff::with_strategy(AtomicStrategy {
+            inner: DefaultStrategy::constant(self.config),
+            shared: self,
+        })
+    }
+
+    /// Returns a new thread local [`BackOff`] handle with a randomized
+    /// exponential back-off strategy, which consults and updates the shared
+    /// contention estimate.
+    #[cfg(feature = "random")]
+    #[inline]
+    pub fn random_backoff(&self) -> BackOff<AtomicStrategy<'_>> {
+        BackOff::with_strategy(AtomicStrategy {
+            inner: DefaultStrategy::random(self.config),
+            shared: self,
+        })
+    }
+
+    #[inline]
+    fn max_contention(&self) -> u32 {
+        let config = &self.config;
+        config.spin_limit_pow.saturating_sub(config.init_pow).saturating_sub(1)
+    }
+
+    #[inline]
+    fn update(&self, f: impl Fn(u32) -> Option<u32>) {
+        let contention = self.contention.get();
+        let mut curr = contention.load(Ordering::Relaxed);
+        while let Some(next) = f(curr) {
+            match contention.compare_exchange_weak(curr, next, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(actual) => curr = actual,
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// AtomicStrategy
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The back-off strategy of thread local handles to an [`AtomicBackOff`].
+#[derive(Clone, Debug)]
+pub struct AtomicStrategy<'a> {
+    inner: DefaultStrategy,
+    shared: &'a AtomicBackOff,
+}
+
+/********** impl BackOffStrategy ******************************************************************/
+
+impl BackOffStrategy for AtomicStrategy<'_> {
+    #[inline]
+    fn spin_steps(&mut self, attempt: u32) -> u32 {
+        self.inner.spin_steps(attempt.saturating_add(self.shared.contention()))
+    }
+
+    #[inline]
+    fn advise_yield(&self, attempt: u32) -> bool {
+        self.inner.advise_yield(attempt.saturating_add(self.shared.contention()))
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[inline]
+    fn yield_limit(&self) -> u32 {
+        self.inner.yield_limit()
+    }
+
+    #[inline]
+    fn max_attempts(&self) -> Option<u32> {
+        self.inner.max_attempts()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn sleep_durations(&self) -> Option<(Duration, Duration)> {
+        self.inner.sleep_durations()
+    }
+
+    #[inline]
+    fn on_success(&mut self) {
+        self.shared.update(|curr| if curr > 0 { Some(curr / 2) } else { None });
+    }
+
+    #[inline]
+    fn on_failure(&mut self) {
+        let max = self.shared.max_contention();
+        self.shared.update(|curr| if curr < max { Some(curr + 1) } else { None });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec::Vec;
+
+    use super::AtomicBackOff;
+    use crate::BackOffConfig;
+
+    #[test]
+    fn shared_contention() {
+        let shared = AtomicBackOff::new();
+        let mut a = shared.backoff();
+        let mut b = shared.backoff();
+
+        for _ in 0..10 {
+            a.failure();
+        }
+
+        let max = BackOffConfig::DEFAULT_SPIN_LIMIT_POW - BackOffConfig::DEFAULT_INIT_POW - 1;
+        assert_eq!(shared.contention(), max);
+        assert!(!b.advise_yield());
+        b.spin();
+        assert!(b.advise_yield());
+
+        b.success();
+        assert_eq!(shared.contention(), max / 2);
+        a.success();
+        a.success();
+        assert_eq!(shared.contention(), 0);
+    }
+
+    #[test]
+    fn concurrent_counter() {
+        const THREADS: usize = 4;
+        const INCREMENTS: usize = 1000;
+
+        let shared = Arc::new((AtomicUsize::new(0), AtomicBackOff::new()));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || {
+                    let (count, backoff) = &*shared;
+                    let mut backoff = backoff.backoff();
+                    for _ in 0..INCREMENTS {
+                        let mut curr = count.load(Ordering::Relaxed);
+                        let mut contended = false;
+                        while let Err(actual) = count.compare_exchange_weak(
+                            curr,
+                            curr + 1,
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                        ) {
+                            curr = actual;
+                            contended = true;
+                            backoff.spin();
+                        }
+
+                        if contended {
+                            backoff.failure();
+                            backoff.reset();
+                        } else {
+                            backoff.success();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(shared.0.load(Ordering::Relaxed), THREADS * INCREMENTS);
+    }
+}
@@ -0,0 +1,77 @@
+//! Hybrid sleeping, which sleeps in the OS for the bulk of a duration and
+//! spins for the remaining slack in order to achieve a high accuracy.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::BackOff;
+
+/// The initial slack before any wake-up jitter has been measured.
+const INIT_SLACK_NANOS: u32 = 200_000;
+/// The upper bound for the slack, regardless of the measured jitter.
+const MAX_SLACK_NANOS: u32 = 10_000_000;
+
+/// The current slack in nanoseconds, which is spun instead of slept.
+static SLACK_NANOS: AtomicU32 = AtomicU32::new(INIT_SLACK_NANOS);
+
+/// Returns the current slack, which is spun instead of slept.
+#[inline]
+pub(super) fn slack() -> Duration {
+    Duration::from_nanos(u64::from(SLACK_NANOS.load(Ordering::Relaxed)))
+}
+
+/// Sleeps until the `deadline` is reached, while spinning for the final slack.
+pub(super) fn sleep_until(deadline: Instant) {
+    let slack = slack();
+    let now = Instant::now();
+
+    if deadline > now + slack {
+        let target = deadline - slack;
+        thread::sleep(target - now);
+
+        let woken = Instant::now();
+        let jitter = if woken > target { woken - target } else { Duration::from_secs(0) };
+        update_slack(jitter);
+    }
+
+    while Instant::now() < deadline {
+        BackOff::spin_once();
+    }
+}
+
+/// Updates the slack based on a measured wake-up `jitter`.
+///
+/// The slack adapts immediately to jitter exceeding it, in order to avoid
+/// late wake-ups, but decays only slowly when the jitter is lower.
+#[inline]
+fn update_slack(jitter: Duration) {
+    let jitter = jitter.as_nanos().min(u128::from(MAX_SLACK_NANOS)) as u32;
+    // add a safety margin of 25% to the measured jitter
+    let sample = jitter + jitter / 4;
+    let slack = SLACK_NANOS.load(Ordering::Relaxed);
+
+    let slack = if sample > slack { sample } else { (slack * 15 + sample) / 16 };
+    SLACK_NANOS.store(slack.min(MAX_SLACK_NANOS), Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::MAX_SLACK_NANOS;
+
+    #[test]
+    fn sleep_until() {
+        let deadline = Instant::now() + Duration::from_millis(2);
+        super::sleep_until(deadline);
+        assert!(Instant::now() >= deadline);
+
+        let slack = super::slack();
+        assert!(slack <= Duration::from_nanos(u64::from(MAX_SLACK_NANOS)));
+
+        let start = Instant::now();
+        super::sleep_until(start - Duration::from_millis(1));
+        assert!(start.elapsed() < Duration::from_millis(1));
+    }
+}
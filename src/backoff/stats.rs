@@ -0,0 +1,202 @@
+//! Opt-in instrumentation of [`BackOff`][crate::BackOff] instances.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::BackOff;
+
+#[cfg(not(target_has_atomic = "ptr"))]
+compile_error!("the `stats` feature requires pointer-sized atomic read-modify-write operations");
+
+static GLOBAL_SPINS: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_SPIN_STEPS: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_ADVISED_YIELDS: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_RESETS: AtomicUsize = AtomicUsize::new(0);
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BackOffStats
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A snapshot of the statistics recorded by a [`BackOff`][crate::BackOff]
+/// instance or of the process-wide aggregate.
+///
+/// # Examples
+///
+/// ```
+/// use conquer_util::{BackOff, BackOffStats};
+///
+/// let mut backoff = BackOff::new();
+/// while !backoff.advise_yield() {
+///     backoff.spin();
+/// }
+///
+/// let stats = backoff.stats();
+/// assert_eq!(stats.spins, 6);
+/// assert_eq!(stats.spin_steps, 2 + 4 + 8 + 16 + 32 + 64);
+/// assert_eq!(stats.advised_yields, 1);
+///
+/// drop(backoff);
+/// assert!(BackOffStats::global().spins >= 6);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub struct BackOffStats {
+    /// The number of calls to [`spin`][crate::BackOff::spin].
+    pub spins: u64,
+    /// The total number of spin steps (iterations) across all calls to
+    /// [`spin`][crate::BackOff::spin].
+    pub spin_steps: u64,
+    /// The number of times [`advise_yield`][crate::BackOff::advise_yield]
+    /// returned `true`.
+    pub advised_yields: u64,
+    /// The number of calls to [`reset`][crate::BackOff::reset].
+    pub resets: u64,
+}
+
+/********** impl inherent *************************************************************************/
+
+impl BackOffStats {
+    /// Returns the process-wide aggregate of the statistics of all
+    /// [`BackOff`][crate::BackOff] instances, which have been dropped so far.
+    ///
+    /// The aggregate counters are `usize` sized, so they may wrap around on
+    /// 32-bit platforms.
+    #[inline]
+    pub fn global() -> Self {
+        Self {
+            spins: GLOBAL_SPINS.load(Ordering::Relaxed) as u64,
+            spin_steps: GLOBAL_SPIN_STEPS.load(Ordering::Relaxed) as u64,
+            advised_yields: GLOBAL_ADVISED_YIELDS.load(Ordering::Relaxed) as u64,
+            resets: GLOBAL_RESETS.load(Ordering::Relaxed) as u64,
+        }
+    }
+
+    /// Resets the process-wide aggregate statistics to zero.
+    #[inline]
+    pub fn reset_global() {
+        GLOBAL_SPINS.store(0, Ordering::Relaxed);
+        GLOBAL_SPIN_STEPS.store(0, Ordering::Relaxed);
+        GLOBAL_ADVISED_YIELDS.store(0, Ordering::Relaxed);
+        GLOBAL_RESETS.store(0, Ordering::Relaxed);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Counters
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The per-instance counters, which are added to the process-wide aggregate
+/// when the instance is dropped.
+#[derive(Debug, Default)]
+pub(super) struct Counters {
+    spins: u64,
+    spin_steps: u64,
+    advised_yields: AtomicUsize,
+    resets: u64,
+}
+
+/********** impl Clone ****************************************************************************/
+
+impl Clone for Counters {
+    /// Clones start out with fresh counters, so that no events are counted
+    /// twice in the aggregate.
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+/********** impl inherent *************************************************************************/
+
+impl Counters {
+    #[inline]
+    pub const fn new() -> Self {
+        Self { spins: 0, spin_steps: 0, advised_yields: AtomicUsize::new(0), resets: 0 }
+    }
+
+    #[inline]
+    pub fn record_spin(&mut self, steps: u32) {
+        self.spins += 1;
+        self.spin_steps += u64::from(steps);
+    }
+
+    #[inline]
+    pub fn record_advised_yield(&self) {
+        self.advised_yields.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn record_reset(&mut self) {
+        self.resets += 1;
+    }
+
+    #[inline]
+    pub fn snapshot(&self) -> BackOffStats {
+        BackOffStats {
+            spins: self.spins,
+            spin_steps: self.spin_steps,
+            advised_yields: self.advised_yields.load(Ordering::Relaxed) as u64,
+            resets: self.resets,
+        }
+    }
+}
+
+/********** impl Drop *****************************************************************************/
+
+impl Drop for Counters {
+    #[inline]
+    fn drop(&mut self) {
+        let stats = self.snapshot();
+        GLOBAL_SPINS.fetch_add(stats.spins as usize, Ordering::Relaxed);
+        GLOBAL_SPIN_STEPS.fetch_add(stats.spin_steps as usize, Ordering::Relaxed);
+        GLOBAL_ADVISED_YIELDS.fetch_add(stats.advised_yields as usize, Ordering::Relaxed);
+        GLOBAL_RESETS.fetch_add(stats.resets as usize, Ordering::Relaxed);
+    }
+}
+
+// the instrumentation must not change the auto traits of `BackOff`, since
+// cargo features have to be additive
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[allow(dead_code)]
+    fn assert_backoff() {
+        assert_send_sync::<BackOff>();
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{BackOff, BackOffConfig};
+
+    #[test]
+    fn instance_stats() {
+        let mut backoff = BackOff::with_config(BackOffConfig::new().spin_limit_pow(4));
+        while !backoff.advise_yield() {
+            backoff.spin();
+        }
+
+        backoff.reset();
+        backoff.spin();
+
+        let stats = backoff.stats();
+        assert_eq!(stats.spins, 4);
+        assert_eq!(stats.spin_steps, 2 + 4 + 8 + 2);
+        assert_eq!(stats.advised_yields, 1);
+        assert_eq!(stats.resets, 1);
+
+        let clone = backoff.clone();
+        assert_eq!(clone.stats(), Default::default());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn internal_calls_not_recorded() {
+        let mut backoff = BackOff::with_config(BackOffConfig::new().spin_limit_pow(4));
+        while !backoff.is_completed() {
+            backoff.snooze();
+        }
+
+        let stats = backoff.stats();
+        assert_eq!(stats.spins, 3);
+        assert_eq!(stats.advised_yields, 0);
+    }
+}
@@ -0,0 +1,581 @@
+//! The [`BackOffStrategy`] trait for customizing the back-off behaviour of
+//! [`BackOff`][crate::BackOff] instances and the default strategy
+//! implementation.
+
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+#[cfg(feature = "random")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::BackOffConfig;
+#[cfg(feature = "random")]
+use super::{BackOffRng, WyRand};
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// BackOffStrategy
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A policy for determining the number of spin steps for each back-off round
+/// of a [`BackOff`][crate::BackOff].
+///
+/// The [`BackOff`][crate::BackOff] keeps track of the index of the current
+/// round (`attempt`), which starts at 0 and is increased with each call to
+/// [`spin`][crate::BackOff::spin] until the strategy advises to yield.
+pub trait BackOffStrategy {
+    /// Returns the number of spin steps for the back-off round with the given
+    /// `attempt` index.
+    fn spin_steps(&mut self, attempt: u32) -> u32;
+
+    /// Returns `true` if further spinning is not advisable after the given
+    /// number of back-off rounds (`attempt`).
+    fn advise_yield(&self, attempt: u32) -> bool;
+
+    /// Resets any internal state of the strategy.
+    ///
+    /// This is called whenever the associated [`BackOff`][crate::BackOff] is
+    /// reset, the default implementation does nothing.
+    #[inline]
+    fn reset(&mut self) {}
+
+    /// Returns the number of times [`snooze`][crate::BackOff::snooze] yields
+    /// the current thread once spinning is no longer advised.
+    #[inline]
+    fn yield_limit(&self) -> u32 {
+        BackOffConfig::DEFAULT_YIELD_LIMIT
+    }
+
+    /// Returns the maximum number of back-off rounds, after which
+    /// [`try_spin`][crate::BackOff::try_spin] fails, or `None` if the number of
+    /// rounds is unlimited (default).
+    #[inline]
+    fn max_attempts(&self) -> Option<u32> {
+        None
+    }
+
+    /// Returns the minimum and maximum durations of the sleep phase of
+    /// [`snooze`][crate::BackOff::snooze] or `None`, if there is no sleep
+    /// phase (default).
+    #[cfg(feature = "std")]
+    #[inline]
+    fn sleep_durations(&self) -> Option<(Duration, Duration)> {
+        None
+    }
+
+    /// Notifies the strategy of a successful operation, e.g. a successful CAS.
+    ///
+    /// This is called by [`BackOff::success`][crate::BackOff::success], the
+    /// default implementation does nothing.
+    #[inline]
+    fn on_success(&mut self) {}
+
+    /// Notifies the strategy of a failed operation, e.g. a failed CAS due to
+    /// contention.
+    ///
+    /// This is called by [`BackOff::failure`][crate::BackOff::failure], the
+    /// default implementation does nothing.
+    #[inline]
+    fn on_failure(&mut self) {}
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// DefaultStrategy
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The default (optionally randomized) exponential back-off strategy, which is
+/// parametrized through a [`BackOffConfig`].
+#[derive(Clone, Debug)]
+pub struct DefaultStrategy {
+    config: BackOffConfig,
+    kind: Kind,
+}
+
+/********** impl Default **************************************************************************/
+
+impl Default for DefaultStrategy {
+    #[inline]
+    fn default() -> Self {
+        Self::constant(BackOffConfig::new())
+    }
+}
+
+/********** impl inherent *************************************************************************/
+
+impl DefaultStrategy {
+    /// Creates a new fixed exponential back-off strategy using the given
+    /// `config`.
+    #[inline]
+    pub const fn constant(config: BackOffConfig) -> Self {
+        Self { config, kind: Kind::Const }
+    }
+
+    /// Returns the [`BackOffConfig`] of the strategy.
+    #[inline]
+    pub fn config(&self) -> &BackOffConfig {
+        &self.config
+    }
+}
+
+#[cfg(feature = "random")]
+impl DefaultStrategy {
+    /// The increment, by which the global seed advances with each call to
+    /// [`random`][DefaultStrategy::random].
+    pub const SEED_INCREMENT: u64 = SEED_INCREMENT as u64;
+
+    /// Creates a new randomized exponential back-off strategy using the given
+    /// `config`.
+    ///
+    /// The seed is taken from a global seed sequence, which advances by
+    /// [`SEED_INCREMENT`][DefaultStrategy::SEED_INCREMENT] with each call.
+    /// By default, the sequence starts at a fixed value, so every run of a
+    /// process produces the same jitter sequences.
+    /// See [`set_global_seed`][DefaultStrategy::set_global_seed] and
+    /// [`seed_global_from_entropy`][DefaultStrategy::seed_global_from_entropy]
+    /// for changing this.
+    #[inline]
+    pub fn random(config: BackOffConfig) -> Self {
+        let seed = GLOBAL_SEED.fetch_add(SEED_INCREMENT, Ordering::Relaxed) as u64;
+        Self::random_with_seed(seed, config)
+    }
+
+    /// Returns the next seed of the global seed sequence without advancing
+    /// it, e.g. for logging it in order to reproduce a particular run.
+    #[inline]
+    pub fn global_seed() -> u64 {
+        GLOBAL_SEED.load(Ordering::Relaxed) as u64
+    }
+
+    /// Restarts the global seed sequence at the given `seed`.
+    ///
+    /// Subsequent calls to [`random`][DefaultStrategy::random] (and all
+    /// functions relying on it, such as [`BackOff::random`][crate::BackOff::random])
+    /// use `seed`, `seed + SEED_INCREMENT` and so on as their seeds, which
+    /// makes e.g. test runs reproducible.
+    /// On 32-bit platforms, only the lower 32 bits of `seed` are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use conquer_util::DefaultStrategy;
+    ///
+    /// DefaultStrategy::set_global_seed(0xdead_beef);
+    /// assert_eq!(DefaultStrategy::global_seed(), 0xdead_beef);
+    /// ```
+    #[inline]
+    pub fn set_global_seed(seed: u64) {
+        GLOBAL_SEED.store(seed as usize, Ordering::Relaxed);
+    }
+
+    /// Restarts the global seed sequence at a seed drawn from the operating
+    /// system's entropy source and returns it.
+    ///
+    /// The seed is read from `/dev/urandom`, if available, and is otherwise
+    /// derived from the randomly keyed hasher of the standard library.
+    /// This function is intended to be called once at start-up, the returned
+    /// seed can be passed to [`set_global_seed`][DefaultStrategy::set_global_seed]
+    /// in order to reproduce a particular run.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn seed_global_from_entropy() -> u64 {
+        let seed = entropy_seed();
+        Self::set_global_seed(seed);
+        seed
+    }
+
+    /// Creates a new randomized exponential back-off strategy using the given
+    /// `seed` value and `config`.
+    #[inline]
+    pub fn random_with_seed(seed: u64, config: BackOffConfig) -> Self {
+        Self { config, kind: Kind::Random(RandomStrategy::new(WyRand::new(seed), config)) }
+    }
+}
+
+/********** impl BackOffStrategy ******************************************************************/
+
+impl BackOffStrategy for DefaultStrategy {
+    #[inline]
+    fn spin_steps(&mut self, attempt: u32) -> u32 {
+        match &mut self.kind {
+            Kind::Const => self.config.steps(self.config.pow(attempt)),
+            #[cfg(feature = "random")]
+            Kind::Random(random) => random.spin_steps(attempt),
+        }
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        #[cfg(feature = "random")]
+        {
+            if let Kind::Random(random) = &mut self.kind {
+                random.reset();
+            }
+        }
+    }
+
+    #[inline]
+    fn advise_yield(&self, attempt: u32) -> bool {
+        self.config.pow(attempt) >= self.config.spin_limit_pow
+    }
+
+    #[inline]
+    fn yield_limit(&self) -> u32 {
+        self.config.yield_limit
+    }
+
+    #[inline]
+    fn max_attempts(&self) -> Option<u32> {
+        self.config.max_attempts
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn sleep_durations(&self) -> Option<(Duration, Duration)> {
+        self.config.sleep
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// RandomStrategy
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// A randomized exponential back-off strategy, which draws its random numbers
+/// from a user-provided random number generator (RNG).
+///
+/// Apart from the source of randomness, this strategy behaves exactly like a
+/// randomized [`DefaultStrategy`], which always uses the built-in [`WyRand`]
+/// generator.
+/// This allows e.g. injecting fixed sequences in tests or re-using existing
+/// (per-thread) generators.
+///
+/// # Examples
+///
+/// ```
+/// use conquer_util::{BackOff, BackOffRng};
+///
+/// /// A "generator" always returning the same value.
+/// struct Fixed(u64);
+///
+/// impl BackOffRng for Fixed {
+///     fn next_u64(&mut self) -> u64 {
+///         self.0
+///     }
+/// }
+///
+/// let mut backoff = BackOff::random_with_rng(Fixed(0));
+/// while !backoff.advise_yield() {
+///     backoff.spin();
+/// }
+/// ```
+#[cfg(feature = "random")]
+#[derive(Clone, Debug)]
+pub struct RandomStrategy<R = WyRand> {
+    config: BackOffConfig,
+    rng: R,
+    prev: u32,
+}
+
+/********** impl inherent *************************************************************************/
+
+#[cfg(feature = "random")]
+impl<R: BackOffRng> RandomStrategy<R> {
+    /// Creates a new randomized exponential back-off strategy using the given
+    /// `rng` and `config`.
+    #[inline]
+    pub fn new(rng: R, config: BackOffConfig) -> Self {
+        let prev = config.steps(config.init_pow);
+        Self { config, rng, prev }
+    }
+
+    /// Returns the [`BackOffConfig`] of the strategy.
+    #[inline]
+    pub fn config(&self) -> &BackOffConfig {
+        &self.config
+    }
+
+    /// Returns a mutable reference to the underlying RNG.
+    #[inline]
+    pub fn rng_mut(&mut self) -> &mut R {
+        &mut self.rng
+    }
+
+    /// Consumes the strategy and returns the underlying RNG.
+    #[inline]
+    pub fn into_rng(self) -> R {
+        self.rng
+    }
+}
+
+/********** impl BackOffStrategy ******************************************************************/
+
+#[cfg(feature = "random")]
+impl<R: BackOffRng> BackOffStrategy for RandomStrategy<R> {
+    #[inline]
+    fn spin_steps(&mut self, attempt: u32) -> u32 {
+        let config = &self.config;
+        let high = u64::from(config.steps(config.pow(attempt)));
+        let (low, high) = match config.jitter {
+            Jitter::Half => (high >> 1, high),
+            Jitter::Full => (0, high + 1),
+            Jitter::Equal => (high >> 1, high + 1),
+            Jitter::Decorrelated => {
+                // a base of zero would never allow the back-off to grow
+                let base = u64::from(config.steps(config.init_pow)).max(1);
+                let cap = u64::from(config.steps(config.spin_limit_pow));
+                (base.min(cap), (u64::from(self.prev) * 3).min(cap) + 1)
+            }
+        };
+
+        let steps = if low < high { self.rng.gen_range(low, high) as u32 } else { high as u32 };
+        self.prev = steps;
+        steps
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.prev = self.config.steps(self.config.init_pow);
+    }
+
+    #[inline]
+    fn advise_yield(&self, attempt: u32) -> bool {
+        self.config.pow(attempt) >= self.config.spin_limit_pow
+    }
+
+    #[inline]
+    fn yield_limit(&self) -> u32 {
+        self.config.yield_limit
+    }
+
+    #[inline]
+    fn max_attempts(&self) -> Option<u32> {
+        self.config.max_attempts
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn sleep_durations(&self) -> Option<(Duration, Duration)> {
+        self.config.sleep
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Adaptive
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// An adaptive strategy, which adjusts the starting point of an inner
+/// strategy based on the reported success or failure of operations.
+///
+/// Each reported failure advances the starting point by one back-off round
+/// (additive increase), whereas each reported success halves the current
+/// advance (multiplicative decrease).
+/// The learned starting point persists when the [`BackOff`][crate::BackOff]
+/// is reset, so that long-lived retry loops on contended memory locations do
+/// not have to ramp up from the beginning on every operation.
+///
+/// The starting point is never advanced so far that the first back-off round
+/// would already advise yielding.
+///
+/// The outcome of each operation should be reported exactly once, while the
+/// individual retries of a contended operation back off as usual.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use conquer_util::BackOff;
+///
+/// let counter = AtomicUsize::new(0);
+/// let mut backoff = BackOff::adaptive();
+///
+/// for _ in 0..10 {
+///     let mut curr = counter.load(Ordering::Relaxed);
+///     let mut contended = false;
+///     while let Err(actual) =
+///         counter.compare_exchange_weak(curr, curr + 1, Ordering::Relaxed, Ordering::Relaxed)
+///     {
+///         curr = actual;
+///         contended = true;
+///         backoff.spin();
+///     }
+///
+///     if contended {
+///         backoff.failure();
+///         backoff.reset();
+///     } else {
+///         backoff.success();
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Adaptive<S = DefaultStrategy> {
+    inner: S,
+    offset: u32,
+}
+
+/********** impl inherent *************************************************************************/
+
+impl<S> Adaptive<S> {
+    /// Creates a new [`Adaptive`] strategy wrapping the given `inner` strategy.
+    #[inline]
+    pub const fn new(inner: S) -> Self {
+        Self { inner, offset: 0 }
+    }
+
+    /// Returns the number of back-off rounds by which the starting point of
+    /// the inner strategy is currently advanced.
+    #[inline]
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Returns a reference to the inner strategy.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+/********** impl BackOffStrategy ******************************************************************/
+
+impl<S: BackOffStrategy> BackOffStrategy for Adaptive<S> {
+    #[inline]
+    fn spin_steps(&mut self, attempt: u32) -> u32 {
+        self.inner.spin_steps(attempt.saturating_add(self.offset))
+    }
+
+    #[inline]
+    fn advise_yield(&self, attempt: u32) -> bool {
+        self.inner.advise_yield(attempt.saturating_add(self.offset))
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[inline]
+    fn yield_limit(&self) -> u32 {
+        self.inner.yield_limit()
+    }
+
+    #[inline]
+    fn max_attempts(&self) -> Option<u32> {
+        self.inner.max_attempts()
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn sleep_durations(&self) -> Option<(Duration, Duration)> {
+        self.inner.sleep_durations()
+    }
+
+    #[inline]
+    fn on_success(&mut self) {
+        self.offset /= 2;
+        self.inner.on_success();
+    }
+
+    #[inline]
+    fn on_failure(&mut self) {
+        let offset = self.offset.saturating_add(1);
+        if !self.inner.advise_yield(offset) {
+            self.offset = offset;
+        }
+
+        self.inner.on_failure();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// global seed
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(all(feature = "random", target_pointer_width = "32"))]
+const INIT_SEED: usize = 0x608c_dbfc;
+#[cfg(all(feature = "random", target_pointer_width = "64"))]
+const INIT_SEED: usize = 0xd1dc_dceb_2fb4_70f3;
+#[cfg(feature = "random")]
+const SEED_INCREMENT: usize = 51;
+
+/// The next seed of the global seed sequence.
+#[cfg(feature = "random")]
+static GLOBAL_SEED: AtomicUsize = AtomicUsize::new(INIT_SEED);
+
+/// Reads a seed from the operating system's entropy source.
+#[cfg(all(feature = "random", feature = "std"))]
+fn entropy_seed() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::fs::File;
+    use std::hash::{BuildHasher, Hasher};
+    use std::io::Read;
+
+    let mut bytes = [0u8; 8];
+    match File::open("/dev/urandom").and_then(|mut file| file.read_exact(&mut bytes)) {
+        Ok(_) => u64::from_le_bytes(bytes),
+        // the keys of each `RandomState` are drawn from the OS' entropy source
+        Err(_) => RandomState::new().build_hasher().finish(),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Kind
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug)]
+enum Kind {
+    Const,
+    #[cfg(feature = "random")]
+    Random(RandomStrategy),
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+// Jitter
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The jitter algorithm used by randomized back-off strategies for drawing
+/// the number of spin steps of each back-off round.
+///
+/// In the following, `steps` denotes the number of steps a non-randomized
+/// strategy would spin for in the current round.
+#[cfg(feature = "random")]
+#[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Jitter {
+    /// Draws uniformly from `[steps / 2, steps)` (default).
+    Half,
+    /// Draws uniformly from `[0, steps]`, which spreads out competing threads
+    /// the furthest, but may occasionally not back off at all.
+    Full,
+    /// Draws uniformly from `[steps / 2, steps]`, which guarantees spinning
+    /// at least half of the non-randomized number of steps.
+    Equal,
+    /// Draws uniformly from `[base, prev * 3]` capped at the spin limit, with
+    /// `base` being the number of steps in the first round (but at least one)
+    /// and `prev` being the number of steps drawn in the previous round.
+    Decorrelated,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Adaptive, BackOffStrategy};
+
+    /// A strategy that never advises yielding.
+    struct Unbounded;
+
+    impl BackOffStrategy for Unbounded {
+        fn spin_steps(&mut self, _: u32) -> u32 {
+            0
+        }
+
+        fn advise_yield(&self, _: u32) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn adaptive_offset_saturates() {
+        let mut adaptive = Adaptive { inner: Unbounded, offset: u32::MAX - 1 };
+        adaptive.on_failure();
+        adaptive.on_failure();
+        assert_eq!(adaptive.offset(), u32::MAX);
+    }
+}
@@ -0,0 +1,165 @@
+//! Low-overhead timing based on the time stamp counter (TSC) of x86_64 CPUs.
+//!
+//! Reading the TSC is considerably cheaper than querying the OS clock, but the
+//! TSC can only be used as a clock, if it is *invariant*, i.e. if it ticks at a
+//! constant rate regardless of the CPU's power state and frequency scaling.
+
+use core::arch::x86_64;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use crate::BackOff;
+
+/// The marker value for an unreliable (non-invariant) TSC.
+#[cfg(feature = "std")]
+const UNRELIABLE: u64 = u64::MAX;
+
+/// The cached TSC frequency in Hz (0 if uncalibrated).
+#[cfg(feature = "std")]
+static TSC_FREQUENCY: AtomicU64 = AtomicU64::new(0);
+
+/// Reads the current value of the TSC.
+#[inline]
+pub(super) fn rdtsc() -> u64 {
+    // SAFETY: the `rdtsc` instruction is available on all x86_64 CPUs
+    unsafe { x86_64::_rdtsc() }
+}
+
+/// Spins until the TSC has advanced by *at least* `cycles` ticks.
+#[inline]
+pub(super) fn spin_for_cycles(cycles: u64) {
+    let start = rdtsc();
+    while rdtsc().wrapping_sub(start) < cycles {
+        BackOff::spin_once();
+    }
+}
+
+/// Returns the calibrated TSC frequency in Hz or `None`, if the TSC is not
+/// invariant and hence unreliable as a clock.
+#[cfg(feature = "std")]
+#[inline]
+pub(super) fn frequency() -> Option<u64> {
+    match TSC_FREQUENCY.load(Ordering::Relaxed) {
+        0 => calibrate(),
+        UNRELIABLE => None,
+        freq => Some(freq),
+    }
+}
+
+/// Converts `dur` into the (rounded up) number of TSC ticks at the given
+/// frequency.
+#[cfg(feature = "std")]
+#[inline]
+pub(super) fn cycles_for(dur: Duration, freq: u64) -> u64 {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+    let scaled = dur.as_nanos() * u128::from(freq);
+    let cycles = if scaled == 0 { 0 } else { (scaled - 1) / NANOS_PER_SEC + 1 };
+    cycles.min(u128::from(u64::MAX)) as u64
+}
+
+/// Returns the calibrated TSC frequency in Hz like [`frequency`], but only
+/// performs the calibration if it fits into the given time `budget`.
+///
+/// Returns `None` if the TSC is unreliable or has not been calibrated yet and
+/// the calibration would take longer than `budget`.
+#[cfg(feature = "std")]
+#[inline]
+pub(super) fn frequency_within(budget: Duration) -> Option<u64> {
+    match TSC_FREQUENCY.load(Ordering::Relaxed) {
+        0 if budget >= Duration::from_nanos(CALIBRATION_NANOS) => calibrate(),
+        0 | UNRELIABLE => None,
+        freq => Some(freq),
+    }
+}
+
+/// The duration of the calibration busy-wait.
+#[cfg(feature = "std")]
+const CALIBRATION_NANOS: u64 = 2_000_000;
+
+#[cfg(feature = "std")]
+#[cold]
+fn calibrate() -> Option<u64> {
+    if !is_invariant() {
+        TSC_FREQUENCY.store(UNRELIABLE, Ordering::Relaxed);
+        return None;
+    }
+
+    let (start, start_tsc) = (Instant::now(), rdtsc());
+    let mut elapsed = start.elapsed();
+    while elapsed < Duration::from_nanos(CALIBRATION_NANOS) {
+        BackOff::spin_once();
+        elapsed = start.elapsed();
+    }
+
+    let cycles = rdtsc().wrapping_sub(start_tsc);
+    let freq = (u128::from(cycles) * 1_000_000_000 / elapsed.as_nanos()) as u64;
+    if freq == 0 || freq == UNRELIABLE {
+        TSC_FREQUENCY.store(UNRELIABLE, Ordering::Relaxed);
+        return None;
+    }
+
+    TSC_FREQUENCY.store(freq, Ordering::Relaxed);
+    Some(freq)
+}
+
+/// Returns `true` if the CPU reports an invariant TSC.
+#[cfg(feature = "std")]
+fn is_invariant() -> bool {
+    const EXT_FEATURES_LEAF: u32 = 0x8000_0000;
+    const POWER_MANAGEMENT_LEAF: u32 = 0x8000_0007;
+    const INVARIANT_TSC_BIT: u32 = 1 << 8;
+
+    // SAFETY: the `cpuid` instruction is available on all x86_64 CPUs
+    #[allow(unused_unsafe)]
+    unsafe {
+        x86_64::__cpuid(EXT_FEATURES_LEAF).eax >= POWER_MANAGEMENT_LEAF
+            && x86_64::__cpuid(POWER_MANAGEMENT_LEAF).edx & INVARIANT_TSC_BIT != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn spin_for_cycles() {
+        let start = super::rdtsc();
+        super::spin_for_cycles(10_000);
+        assert!(super::rdtsc().wrapping_sub(start) >= 10_000);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn frequency() {
+        use std::time::{Duration, Instant};
+
+        match super::frequency() {
+            Some(freq) => {
+                assert!(freq > 100_000_000);
+                assert_eq!(super::cycles_for(Duration::from_secs(1), freq), freq);
+
+                let start = Instant::now();
+                super::spin_for_cycles(super::cycles_for(Duration::from_micros(100), freq));
+                assert!(start.elapsed() >= Duration::from_micros(99));
+            }
+            None => assert!(!super::is_invariant()),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn frequency_within() {
+        use std::time::{Duration, Instant};
+
+        // a short budget never blocks for the calibration
+        let start = Instant::now();
+        let freq = super::frequency_within(Duration::from_nanos(100));
+        if freq.is_none() {
+            assert!(start.elapsed() < Duration::from_nanos(super::CALIBRATION_NANOS));
+        }
+
+        let freq = super::frequency_within(Duration::from_millis(10));
+        assert_eq!(freq.is_some(), super::frequency().is_some());
+    }
+}
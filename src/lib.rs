@@ -38,14 +38,62 @@
 //! back-off in e.g. spin-loops.
 //! This type is `#![no_std]` compatible, but provides additional features when
 //! the `std` feature is also enabled.
+//! The [`AtomicBackOff`][crate::AtomicBackOff] type can be embedded in
+//! concurrent data structures in order to share a contention estimate between
+//! all accessing threads, on platforms supporting 32-bit atomic
+//! compare-and-swap operations.
+//! Note that enabling the `back-off` feature also enables the `align` feature.
+//! The parameters of the back-off can be tuned for the targeted platform
+//! through the `const` constructible [`BackOffConfig`][crate::BackOffConfig]
+//! type.
+//! For the tightest loops, [`ConstBackOff`][crate::ConstBackOff] fixes its
+//! exponents at compile-time through const generic parameters instead.
 //!
 //! ### Randomized Exponential Back-Off
 //!
-//! Enabling the `random` feature in addition to the `back-off` feature pulls in
-//! the `rand` dependency and additionally adds `#![no_std]` compatible
-//! randomized exponential back-off, which adds some slight variations the time
-//! each thread spends spinning.
+//! Enabling the `random` feature in addition to the `back-off` feature adds
+//! `#![no_std]` compatible randomized exponential back-off, which adds some
+//! slight variations the time each thread spends spinning.
 //! This may help avoid issues such as *convoying*.
+//! The random numbers are drawn from the built-in, dependency-free
+//! [`WyRand`][crate::WyRand] generator.
+//! Custom generators can be used through the
+//! [`BackOffRng`][crate::BackOffRng] trait and the
+//! [`RandomStrategy`][crate::RandomStrategy].
+//! Enabling the optional `rand` feature (which implies `random`) additionally
+//! implements the traits of the `rand` crate for the built-in generator and
+//! allows using all generators of the `rand` crate through the
+//! [`RandRng`][crate::RandRng] adapter.
+//!
+//! ### Async Back-Off
+//!
+//! Enabling the `async` feature makes
+//! [`BackOff::snooze_async`][crate::BackOff::snooze_async] available, which
+//! spins inline at first, but yields the current task back to its executor
+//! instead of blocking the executor's thread, once spinning is no longer
+//! advisable.
+//! It does not depend on any particular executor and is `#![no_std]`
+//! compatible.
+//!
+//! ### Hardware-Assisted Waiting
+//!
+//! Enabling the `hw-wait` feature makes
+//! [`BackOff::wait_on_address_change`][crate::BackOff::wait_on_address_change]
+//! and timed pauses available, which use dedicated CPU instructions to wait in
+//! a low-power state (`umwait`/`tpause` on x86_64 CPUs with the WAITPKG
+//! extension, `wfe` on AArch64), if supported.
+//! Otherwise, these functions fall back to regular spinning.
+//!
+//! ### Statistics
+//!
+//! Enabling the `stats` feature records statistics such as the number of spin
+//! rounds and steps for each [`BackOff`][crate::BackOff] instance, which can be
+//! queried through [`BackOff::stats`][crate::BackOff::stats].
+//! A process-wide aggregate of all dropped instances is available through
+//! [`BackOffStats::global`][crate::BackOffStats::global].
+//! Without this feature, no instrumentation code is compiled at all.
+//! The counters are atomic, so this feature requires a platform supporting
+//! pointer-sized atomic read-modify-write operations.
 //!
 //! ## TLS
 //!
@@ -67,7 +115,24 @@ mod backoff;
 #[cfg(feature = "tls")]
 mod local;
 
+#[cfg(feature = "hw-wait")]
+pub use crate::backoff::AtomicWait;
+#[cfg(feature = "stats")]
+pub use crate::backoff::BackOffStats;
+#[cfg(feature = "rand")]
+pub use crate::backoff::RandRng;
+#[cfg(feature = "async")]
+pub use crate::backoff::SnoozeFuture;
 #[cfg(feature = "back-off")]
-pub use crate::backoff::BackOff;
+pub use crate::backoff::{
+    Adaptive, AtomicRetry, BackOff, BackOffConfig, BackOffExhausted, BackOffStrategy, Clock,
+    ConstBackOff, DefaultStrategy, Growth, TimeoutError,
+};
+#[cfg(all(feature = "back-off", target_has_atomic = "32"))]
+pub use crate::backoff::{AtomicBackOff, AtomicStrategy};
+#[cfg(feature = "random")]
+pub use crate::backoff::{BackOffRng, Jitter, RandomStrategy, WyRand};
+#[cfg(all(feature = "back-off", feature = "std"))]
+pub use crate::backoff::{Snooze, StdClock};
 #[cfg(feature = "tls")]
 pub use crate::local::{BoundedThreadLocal, BoundsError, IntoIter, Local, Token};